@@ -0,0 +1,16 @@
+-- Switches heading numbering to the appendix pattern at the first `{.appendix}` heading and restarts the counter.
+local pattern = os.getenv("QUOIN_APPENDIX_NUMBERING") or "A.1"
+local started = false
+
+local typst_string = require("quoin_typst").string
+
+function Header(el)
+  if started or not el.classes:includes("appendix") then
+    return nil
+  end
+  started = true
+  return {
+    pandoc.RawBlock("typst", "#counter(heading).update(0)\n#set heading(numbering: " .. typst_string(pattern) .. ")"),
+    el,
+  }
+end
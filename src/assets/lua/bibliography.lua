@@ -0,0 +1,97 @@
+-- Places the reference list right after a "References" heading, or at the end of the document.
+-- With citeproc this inserts the `#refs` div; in Typst mode it emits Typst's native `#bibliography`.
+local mode = os.getenv("QUOIN_CITATIONS") or "citeproc"
+-- Typst resolves paths against the .typ file (absolute ones against its project root)
+local typst_dir = os.getenv("QUOIN_TYPST_DIR")
+
+local marker_titles = {
+  ["references"] = true, ["bibliography"] = true, ["works cited"] = true,
+  ["références"] = true, ["bibliographie"] = true,
+  ["literatur"] = true, ["literaturverzeichnis"] = true,
+  ["referencias"] = true, ["bibliografía"] = true,
+  ["riferimenti"] = true, ["bibliografia"] = true, ["referências"] = true,
+}
+
+local typst_string = require("quoin_typst").string
+
+local function file_exists(path)
+  local file = io.open(path, "rb")
+  if file then
+    file:close()
+    return true
+  end
+  return false
+end
+
+-- Looks a file up like citeproc does (next to the input, then along the resource path) and makes the
+-- result relative to the .typ file
+local function typst_file(path)
+  path = pandoc.utils.stringify(path)
+  if not typst_dir then
+    return typst_string(path)
+  end
+  local resolved = path
+  if pandoc.path.is_relative(path) then
+    for _, dir in ipairs(PANDOC_STATE.resource_path) do
+      local candidate = pandoc.path.join({ dir, path })
+      if file_exists(candidate) then
+        resolved = candidate
+        break
+      end
+    end
+  end
+  if pandoc.path.is_relative(resolved) then
+    return typst_string(resolved)
+  end
+  return typst_string(pandoc.path.make_relative(resolved, typst_dir, true))
+end
+
+local function bibliography_paths(value)
+  local paths = {}
+  if pandoc.utils.type(value) == "List" then
+    for _, item in ipairs(value) do
+      table.insert(paths, typst_file(item))
+    end
+  else
+    table.insert(paths, typst_file(value))
+  end
+  return paths
+end
+
+function Pandoc(doc)
+  local marker = nil
+  for i, block in ipairs(doc.blocks) do
+    if block.t == "Div" and block.identifier == "refs" then
+      -- An explicit `::: {#refs}` placement always wins.
+      return nil
+    end
+    if block.t == "Header" and marker_titles[pandoc.text.lower(pandoc.utils.stringify(block))] then
+      marker = i
+    end
+  end
+
+  if mode ~= "typst" then
+    if marker then
+      table.insert(doc.blocks, marker + 1, pandoc.Div({}, pandoc.Attr("refs")))
+    end
+    return doc
+  end
+
+  if not doc.meta.bibliography then
+    return nil
+  end
+  local args = "(" .. table.concat(bibliography_paths(doc.meta.bibliography), ", ") .. ",)"
+  if doc.meta.csl then
+    args = args .. ", style: " .. typst_file(doc.meta.csl)
+  end
+  if marker then
+    -- The marker heading already provides the title.
+    table.insert(doc.blocks, marker + 1, pandoc.RawBlock("typst", "#bibliography(" .. args .. ", title: none)"))
+  else
+    table.insert(doc.blocks, pandoc.RawBlock("typst", "#bibliography(" .. args .. ")"))
+  end
+  -- Keep the template from emitting a second bibliography.
+  doc.meta.bibliography = nil
+  doc.meta.csl = nil
+  return doc
+end
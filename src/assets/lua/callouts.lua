@@ -0,0 +1,53 @@
+-- Turns GitHub-style alerts (`> [!NOTE]`) and fenced divs (`::: note`) into `quoin-callout` boxes.
+local kinds = { note = true, tip = true, important = true, warning = true, caution = true }
+
+local typst_string = require("quoin_typst").string
+
+local function callout(kind, title, blocks)
+  local args = typst_string(kind)
+  if title and title ~= "" then
+    args = args .. ", title: " .. typst_string(title)
+  end
+  local result = { pandoc.RawBlock("typst", "#quoin-callout(" .. args .. ")[") }
+  for _, block in ipairs(blocks) do
+    table.insert(result, block)
+  end
+  table.insert(result, pandoc.RawBlock("typst", "]"))
+  return result
+end
+
+function Div(el)
+  for _, class in ipairs(el.classes) do
+    local kind = class:lower()
+    if kinds[kind] then
+      local blocks = el.content
+      -- Pandoc's `alerts` extension prepends a title div; the box draws its own title.
+      if blocks[1] and blocks[1].t == "Div" and blocks[1].classes:includes("title") then
+        table.remove(blocks, 1)
+      end
+      return callout(kind, el.attributes["title"], blocks)
+    end
+  end
+end
+
+function BlockQuote(el)
+  local first = el.content[1]
+  if not first or first.t ~= "Para" or not first.content[1] or first.content[1].t ~= "Str" then
+    return nil
+  end
+  local marker = first.content[1].text:match("^%[!(%a+)%]$")
+  if not marker or not kinds[marker:lower()] then
+    return nil
+  end
+
+  local inlines = first.content
+  table.remove(inlines, 1)
+  while inlines[1] and (inlines[1].t == "SoftBreak" or inlines[1].t == "LineBreak" or inlines[1].t == "Space") do
+    table.remove(inlines, 1)
+  end
+  local blocks = el.content
+  if #inlines == 0 then
+    table.remove(blocks, 1)
+  end
+  return callout(marker:lower(), nil, blocks)
+end
@@ -0,0 +1,35 @@
+-- Draws the `quoin-cheat-box` divs made by cheat_sheet.lua: `#quoin-cheat-box(index)[heading][content]`.
+-- The heading may set `color` to a callout kind (note, tip, important, warning, caution) or a hex color, and
+-- `{.breakable}` lets a long section continue in the next column.
+local kinds = { note = true, tip = true, important = true, warning = true, caution = true }
+
+local function color_argument(color)
+  if not color then
+    return ""
+  end
+  if kinds[color] then
+    return ", color: quoin-theme.callouts." .. color
+  end
+  if color:match("^#%x%x%x$") or color:match("^#%x%x%x%x%x%x$") then
+    return ', color: rgb("' .. color .. '")'
+  end
+  io.stderr:write("[quoin] Warning: ignoring invalid cheat-sheet section color '" .. color .. "'\n")
+  return ""
+end
+
+function Div(el)
+  if not el.classes:includes("quoin-cheat-box") then
+    return nil
+  end
+  local heading = table.remove(el.content, 1)
+  local args = el.attributes["index"] .. color_argument(heading.attributes["color"])
+  if heading.classes:includes("breakable") then
+    args = args .. ", breakable: true"
+  end
+  local result = { pandoc.RawBlock("typst", "#quoin-cheat-box(" .. args .. ")["), heading, pandoc.RawBlock("typst", "][") }
+  for _, block in ipairs(el.content) do
+    table.insert(result, block)
+  end
+  table.insert(result, pandoc.RawBlock("typst", "]"))
+  return result
+end
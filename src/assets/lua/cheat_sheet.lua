@@ -0,0 +1,31 @@
+-- Groups each H2 and the blocks up to the next H1 or H2 into a `quoin-cheat-box` div, numbered for color
+-- coding, and lets H1 headings span all columns. Runs before the column filter; boxes are drawn by
+-- cheat_boxes.lua once the other filters are done.
+local function section_end(block)
+  return block.t == "Header" and block.level <= 2
+end
+
+function Pandoc(doc)
+  local blocks, box = {}, nil
+  local count = 0
+  for _, block in ipairs(doc.blocks) do
+    if section_end(block) then
+      box = nil
+    end
+    if block.t == "Header" and block.level == 1 and not block.classes:includes("span") then
+      block.classes:insert("span")
+    end
+    if block.t == "Header" and block.level == 2 then
+      local attributes = { index = tostring(count) }
+      count = count + 1
+      box = pandoc.Div({ block }, pandoc.Attr("", { "quoin-cheat-box" }, attributes))
+      table.insert(blocks, box)
+    elseif box then
+      box.content:insert(block)
+    else
+      table.insert(blocks, block)
+    end
+  end
+  doc.blocks = blocks
+  return doc
+end
@@ -0,0 +1,61 @@
+-- Passes per-block code options to the Typst code block style:
+-- `{.rust linenos=true hl_lines="3-5" title="src/main.rs" startFrom=10 wrap=false}`.
+-- Pandoc's `.numberLines` class is honored as well. Highlighted lines use the displayed numbers.
+
+local typst_string = require("quoin_typst").string
+
+local function typst_bool(value)
+  return (value == "false" or value == "no" or value == "0") and "false" or "true"
+end
+
+-- "3-5, 8" -> "(3, 4, 5, 8,)"
+local function line_list(spec)
+  local lines = {}
+  for token in spec:gmatch("[^,%s]+") do
+    local from, to = token:match("^(%d+)%-(%d+)$")
+    if from then
+      for n = tonumber(from), tonumber(to) do
+        table.insert(lines, tostring(n))
+      end
+    elseif token:match("^%d+$") then
+      table.insert(lines, token)
+    end
+  end
+  return "(" .. table.concat(lines, ", ") .. ",)"
+end
+
+function CodeBlock(el)
+  local attrs = el.attributes
+  local options = {}
+
+  local linenos = attrs.linenos or attrs["line-numbers"]
+  if linenos then
+    table.insert(options, "linenos: " .. typst_bool(linenos))
+  elseif el.classes:includes("numberLines") or el.classes:includes("number-lines") then
+    table.insert(options, "linenos: true")
+  end
+  local start = attrs.startFrom or attrs.linenostart or attrs.start
+  if start and start:match("^%-?%d+$") then
+    table.insert(options, "start: " .. start)
+  end
+  local highlight = attrs.hl_lines or attrs["hl-lines"] or attrs.highlight
+  if highlight then
+    table.insert(options, "highlight: " .. line_list(highlight))
+  end
+  local title = attrs.title or attrs.filename
+  if title then
+    table.insert(options, "title: " .. typst_string(title))
+  end
+  if attrs.wrap then
+    table.insert(options, "wrap: " .. typst_bool(attrs.wrap))
+  end
+
+  if #options == 0 then
+    return nil
+  end
+  return {
+    pandoc.RawBlock("typst", "#quoin-code(" .. table.concat(options, ", ") .. ")["),
+    el,
+    pandoc.RawBlock("typst", "]"),
+  }
+end
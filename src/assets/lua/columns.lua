@@ -0,0 +1,119 @@
+-- Splits the body into `#quoin-columns[...]` sections. Spanning elements run across the full width in between:
+-- headings, divs, figures and tables with the `span` class, `::: landscape` and `::: portrait` pages, `{.appendix}`
+-- headings (their numbering rule must stay at the top level), level-1 headings when they start a new page (Typst
+-- cannot break pages inside a container) and, unless disabled, tables too wide for a column.
+local count = tonumber(os.getenv("QUOIN_COLUMNS") or "1") or 1
+local span_wide = os.getenv("QUOIN_SPAN_WIDE_TABLES") ~= "0"
+local h1_page_breaks = os.getenv("QUOIN_H1_PAGE_BREAKS") == "1"
+-- Rough number of characters on a full-width line; a table wider than a column's share spans
+local line_chars = 90
+
+local function text_length(blocks)
+  local text = pandoc.utils.stringify(blocks)
+  return utf8.len(text) or #text
+end
+
+-- Widest cell per column, plus padding, in characters
+local function table_width(tbl)
+  local widths = {}
+  local function measure(rows)
+    for _, row in ipairs(rows) do
+      for i, cell in ipairs(row.cells) do
+        widths[i] = math.max(widths[i] or 0, text_length(cell.contents))
+      end
+    end
+  end
+  measure(tbl.head.rows)
+  for _, body in ipairs(tbl.bodies) do
+    measure(body.head)
+    measure(body.body)
+  end
+  measure(tbl.foot.rows)
+  local total = 0
+  for _, width in ipairs(widths) do
+    total = total + width + 3
+  end
+  return total
+end
+
+local function is_wide(block)
+  if not span_wide then
+    return false
+  end
+  if block.t == "Table" then
+    return table_width(block) > line_chars / count
+  end
+  if block.t == "Div" or block.t == "Figure" then
+    for _, child in ipairs(block.content) do
+      if child.t == "Table" and is_wide(child) then
+        return true
+      end
+    end
+  end
+  return false
+end
+
+local function spans(block)
+  if block.t == "Header" then
+    return block.classes:includes("span") or block.classes:includes("appendix")
+      or (h1_page_breaks and block.level == 1)
+  end
+  if block.t == "Div" or block.t == "Figure" or block.t == "Table" then
+    local classes = block.classes
+    return classes:includes("span") or classes:includes("landscape") or classes:includes("portrait")
+      or is_wide(block)
+  end
+  return false
+end
+
+local function is_orientation(block)
+  return block.t == "Div" and (block.classes:includes("landscape") or block.classes:includes("portrait"))
+end
+
+-- Orientation blocks get pages of their own, which Typst cannot start inside another block
+local function check_nesting(block)
+  local outer = is_orientation(block) and "::: " .. block.classes[1] or "another block"
+  local function nested(div)
+    if is_orientation(div) then
+      error("[quoin] a ::: " .. div.classes[1] .. " block inside " .. outer .. " cannot be laid out in columns;"
+        .. " move it to the top level", 0)
+    end
+  end
+  if block.t == "Div" or block.t == "Figure" then
+    for _, child in ipairs(block.content) do
+      pandoc.walk_block(pandoc.Div(child), { Div = nested })
+    end
+  else
+    pandoc.walk_block(pandoc.Div(block), { Div = nested })
+  end
+end
+
+function Pandoc(doc)
+  if count < 2 then
+    return nil
+  end
+  local blocks, section = {}, {}
+  local function flush()
+    if #section == 0 then
+      return
+    end
+    table.insert(blocks, pandoc.RawBlock("typst", "#quoin-columns["))
+    for _, block in ipairs(section) do
+      table.insert(blocks, block)
+    end
+    table.insert(blocks, pandoc.RawBlock("typst", "]"))
+    section = {}
+  end
+  for _, block in ipairs(doc.blocks) do
+    check_nesting(block)
+    if spans(block) then
+      flush()
+      table.insert(blocks, block)
+    else
+      table.insert(section, block)
+    end
+  end
+  flush()
+  doc.blocks = blocks
+  return doc
+end
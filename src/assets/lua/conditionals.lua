@@ -0,0 +1,291 @@
+-- Keeps or drops fenced divs with a `when` attribute, e.g. `::: {when="audience == 'internal'"}`.
+--
+-- Expressions support variables (`-V` values first, then metadata; dotted names for nested fields),
+-- 'strings', numbers, true/false, [lists], ==, !=, in, not in, and, or, not and parentheses.
+-- A bare variable is a boolean flag.
+local strict = os.getenv("QUOIN_STRICT_VARIABLES") == "1"
+
+local overrides = {}
+for line in (os.getenv("QUOIN_VARIABLES") or ""):gmatch("[^\n]+") do
+  local key, value = line:match("^([^=]+)=(.*)$")
+  if key then
+    overrides[key] = value
+  end
+end
+
+local meta = {}
+
+local function from_meta(value)
+  if type(value) == "boolean" then
+    return value
+  end
+  local kind = pandoc.utils.type(value)
+  if kind == "List" then
+    local items = {}
+    for _, item in ipairs(value) do
+      table.insert(items, from_meta(item))
+    end
+    return items
+  end
+  if kind == "table" or kind == "Meta" then
+    return value
+  end
+  return pandoc.utils.stringify(value)
+end
+
+local function lookup(name)
+  if overrides[name] ~= nil then
+    return overrides[name]
+  end
+  local value = meta
+  for part in name:gmatch("[^.]+") do
+    if type(value) ~= "table" then
+      return nil
+    end
+    value = value[part]
+    if value == nil then
+      return nil
+    end
+  end
+  return from_meta(value)
+end
+
+local function tokenize(expr)
+  local tokens, i = {}, 1
+  while i <= #expr do
+    local c = expr:sub(i, i)
+    if c:match("%s") then
+      i = i + 1
+    elseif c == "'" or c == '"' then
+      local close = expr:find(c, i + 1, true)
+      if not close then
+        return nil, "unterminated string"
+      end
+      table.insert(tokens, { kind = "string", value = expr:sub(i + 1, close - 1) })
+      i = close + 1
+    elseif expr:sub(i, i + 1) == "==" or expr:sub(i, i + 1) == "!=" or expr:sub(i, i + 1) == "&&"
+        or expr:sub(i, i + 1) == "||" then
+      table.insert(tokens, { kind = "op", value = expr:sub(i, i + 1) })
+      i = i + 2
+    elseif c:match("[%(%)%[%],!]") then
+      table.insert(tokens, { kind = "op", value = c })
+      i = i + 1
+    else
+      local word = expr:match("^[%w_%.%-]+", i)
+      if not word then
+        return nil, "unexpected '" .. c .. "'"
+      end
+      if word == "and" or word == "or" or word == "not" or word == "in" then
+        table.insert(tokens, { kind = "op", value = word })
+      elseif word == "true" or word == "false" then
+        table.insert(tokens, { kind = "bool", value = word == "true" })
+      elseif word:match("^%-?%d+%.?%d*$") then
+        table.insert(tokens, { kind = "string", value = word })
+      else
+        table.insert(tokens, { kind = "name", value = word })
+      end
+      i = i + #word
+    end
+  end
+  return tokens
+end
+
+local function truthy(value)
+  if type(value) == "table" then
+    return #value > 0
+  end
+  if type(value) == "string" then
+    local lower = value:lower()
+    return lower ~= "" and lower ~= "false" and lower ~= "no" and lower ~= "0" and lower ~= "off"
+  end
+  return value == true
+end
+
+local function text(value)
+  if value == nil then
+    return nil
+  end
+  return tostring(value)
+end
+
+local function members(value)
+  if type(value) == "table" then
+    return value
+  end
+  local items = {}
+  for item in (text(value) or ""):gmatch("[^,]+") do
+    table.insert(items, (item:gsub("^%s+", ""):gsub("%s+$", "")))
+  end
+  return items
+end
+
+-- Recursive descent evaluator; unknown variables evaluate to nil and are collected
+local function evaluate(expr)
+  local tokens, err = tokenize(expr)
+  if not tokens then
+    return nil, err, {}
+  end
+  local pos, unknown = 1, {}
+
+  local function peek(value)
+    local token = tokens[pos]
+    return token and token.kind == "op" and token.value == value
+  end
+
+  local function expect(value)
+    if not peek(value) then
+      error("expected '" .. value .. "'", 0)
+    end
+    pos = pos + 1
+  end
+
+  local parse_or
+
+  local function parse_value()
+    local token = tokens[pos]
+    if not token then
+      error("unexpected end of expression", 0)
+    end
+    pos = pos + 1
+    if token.kind == "string" or token.kind == "bool" then
+      return token.value
+    elseif token.kind == "name" then
+      local value = lookup(token.value)
+      if value == nil then
+        table.insert(unknown, token.value)
+      end
+      return value
+    elseif token.value == "(" then
+      local value = parse_or()
+      expect(")")
+      return value
+    elseif token.value == "[" then
+      local items = {}
+      while not peek("]") do
+        table.insert(items, text(parse_value()))
+        if not peek("]") then
+          expect(",")
+        end
+      end
+      expect("]")
+      return items
+    end
+    error("unexpected '" .. tostring(token.value) .. "'", 0)
+  end
+
+  local function parse_comparison()
+    local left = parse_value()
+    if peek("==") or peek("!=") then
+      local negate = tokens[pos].value == "!="
+      pos = pos + 1
+      local right = parse_value()
+      local equal = left ~= nil and right ~= nil and text(left) == text(right)
+      return equal ~= negate
+    end
+    local negate = false
+    if peek("not") and tokens[pos + 1] and tokens[pos + 1].value == "in" then
+      negate = true
+      pos = pos + 1
+    end
+    if peek("in") then
+      pos = pos + 1
+      local found = false
+      for _, item in ipairs(members(parse_value())) do
+        if left ~= nil and text(item) == text(left) then
+          found = true
+        end
+      end
+      return found ~= negate
+    end
+    return left
+  end
+
+  local function parse_not()
+    if peek("not") or peek("!") then
+      pos = pos + 1
+      return not truthy(parse_not())
+    end
+    return parse_comparison()
+  end
+
+  local function parse_and()
+    local value = parse_not()
+    while peek("and") or peek("&&") do
+      pos = pos + 1
+      local right = parse_not()
+      value = truthy(value) and truthy(right)
+    end
+    return value
+  end
+
+  parse_or = function()
+    local value = parse_and()
+    while peek("or") or peek("||") do
+      pos = pos + 1
+      local right = parse_and()
+      value = truthy(value) or truthy(right)
+    end
+    return value
+  end
+
+  local ok, result = pcall(function()
+    local value = parse_or()
+    if tokens[pos] then
+      error("unexpected '" .. tostring(tokens[pos].value) .. "'", 0)
+    end
+    return value
+  end)
+  if not ok then
+    return nil, result, unknown
+  end
+  return truthy(result), nil, unknown
+end
+
+local function report(message)
+  if strict then
+    error("[quoin] " .. message, 0)
+  end
+  io.stderr:write("[quoin] Warning: " .. message .. "\n")
+end
+
+-- A kept block keeps its classes, id and other attributes (callouts, orientation, cross-references, ...);
+-- a div that only had the condition is unwrapped
+local function kept(el)
+  el.attributes["when"] = nil
+  if el.identifier == "" and #el.classes == 0 and #el.attributes == 0 then
+    return el.content
+  end
+  return el
+end
+
+local function Div(el)
+  local condition = el.attributes["when"]
+  if not condition then
+    return nil
+  end
+  local keep, err, unknown = evaluate(condition)
+  if err then
+    report("invalid condition when=\"" .. condition .. "\": " .. err .. "; keeping the block")
+    return kept(el)
+  end
+  if #unknown > 0 then
+    report("unknown variable(s) " .. table.concat(unknown, ", ") .. " in when=\"" .. condition
+      .. "\"; they are treated as unset")
+  end
+  if keep then
+    return kept(el)
+  end
+  return {}, false
+end
+
+return {
+  {
+    Meta = function(m)
+      meta = m
+    end,
+  },
+  {
+    traverse = "topdown",
+    Div = Div,
+  },
+}
@@ -0,0 +1,187 @@
+-- Numbered, clickable cross-references. Elements labelled `{#fig:x}`, `{#tbl:x}`, `{#eq:x}`, `{#sec:x}` or
+-- `{#lst:x}` become Typst labels, and `@fig:x` references become Typst references.
+-- References to unknown labels are reported as warnings and rendered as "??".
+local prefixes = { fig = true, tbl = true, eq = true, sec = true, lst = true }
+
+local function is_crossref(id)
+  local prefix = id and id:match("^(%a+):.")
+  return prefix ~= nil and prefixes[prefix] == true
+end
+
+local function warn(message)
+  io.stderr:write("[quoin] Warning: " .. message .. "\n")
+end
+
+local function caption_inlines(text)
+  if not text or text == "" then
+    return {}
+  end
+  return pandoc.utils.blocks_to_inlines(pandoc.read(text, "commonmark").blocks)
+end
+
+local function figure(content, caption, id)
+  return pandoc.Figure(content, { pandoc.Plain(caption) }, pandoc.Attr(id))
+end
+
+local function equation_label(id)
+  return pandoc.RawInline("typst", " <" .. id .. ">")
+end
+
+local function is_display_math(el)
+  return el and el.t == "Math" and el.mathtype == "DisplayMath"
+end
+
+-- Attach labels written after display math (`$$ ... $$ {#eq:x}`) to the equation.
+local function label_equations(inlines)
+  local result = pandoc.List()
+  local i = 1
+  while i <= #inlines do
+    local el = inlines[i]
+    if el.t == "Span" and is_crossref(el.identifier) and #el.content == 1 and is_display_math(el.content[1]) then
+      result:insert(el.content[1])
+      result:insert(equation_label(el.identifier))
+    elseif is_display_math(el) then
+      result:insert(el)
+      local j = i + 1
+      while inlines[j] and (inlines[j].t == "Space" or inlines[j].t == "SoftBreak") do
+        j = j + 1
+      end
+      local id = inlines[j] and inlines[j].t == "Str" and inlines[j].text:match("^{#([^}%s]+)}$")
+      if is_crossref(id) then
+        result:insert(equation_label(id))
+        i = j
+      end
+    else
+      result:insert(el)
+    end
+    i = i + 1
+  end
+  return result
+end
+
+local normalize = {
+  Para = function(el)
+    local image = el.content[1]
+    if #el.content == 1 and image.t == "Image" and is_crossref(image.identifier) then
+      local id = image.identifier
+      image.identifier = ""
+      return figure({ pandoc.Plain({ image }) }, image.caption, id)
+    end
+    el.content = label_equations(el.content)
+    return el
+  end,
+  Figure = function(el)
+    if el.identifier ~= "" then
+      return nil
+    end
+    -- Implicit figures keep the label on the image; Typst needs it on the figure.
+    local id = nil
+    el.content = el.content:walk({
+      Image = function(image)
+        if is_crossref(image.identifier) then
+          id = image.identifier
+          image.identifier = ""
+          return image
+        end
+      end,
+    })
+    if id then
+      el.identifier = id
+      return el
+    end
+  end,
+  Div = function(el)
+    local inner = el.content[1]
+    if not is_crossref(el.identifier) or #el.content ~= 1 then
+      return nil
+    end
+    if inner.t == "Table" then
+      inner.identifier = el.identifier
+      if el.attributes.caption then
+        inner.caption = { long = { pandoc.Plain(caption_inlines(el.attributes.caption)) } }
+      end
+      return inner
+    elseif inner.t == "CodeBlock" then
+      return figure({ inner }, caption_inlines(el.attributes.caption), el.identifier)
+    end
+  end,
+  CodeBlock = function(el)
+    if not is_crossref(el.identifier) then
+      return nil
+    end
+    local id = el.identifier
+    el.identifier = ""
+    return figure({ el }, caption_inlines(el.attributes.caption), id)
+  end,
+}
+
+local function reference(id, labels)
+  if labels[id] then
+    return pandoc.RawInline("typst", "#ref(<" .. id .. ">)")
+  end
+  warn("unresolved cross-reference @" .. id)
+  return pandoc.Strong({ pandoc.Str("??") })
+end
+
+function Pandoc(doc)
+  doc = doc:walk(normalize)
+
+  local labels = {}
+  local function collect(el)
+    if el.identifier and el.identifier ~= "" then
+      labels[el.identifier] = true
+    end
+  end
+  doc:walk({
+    Header = collect, Figure = collect, Table = collect, Div = collect, CodeBlock = collect,
+    RawInline = function(el)
+      local id = el.format == "typst" and el.text:match("^ <([^>]+)>$")
+      if id then
+        labels[id] = true
+      end
+    end,
+  })
+
+  return doc:walk({
+    Cite = function(el)
+      for _, citation in ipairs(el.citations) do
+        if not is_crossref(citation.id) then
+          return nil
+        end
+      end
+      local result = pandoc.List()
+      for i, citation in ipairs(el.citations) do
+        if i > 1 then
+          result:extend({ pandoc.Str(","), pandoc.Space() })
+        end
+        result:insert(reference(citation.id, labels))
+      end
+      return result
+    end,
+    Str = function(el)
+      if not el.text:find("@%a+:") then
+        return nil
+      end
+      local result = pandoc.List()
+      local rest = el.text
+      while true do
+        local before, id, after = rest:match("^(.-)@(%a+:[%w_%-%.:]*[%w_])(.*)$")
+        if not id or not is_crossref(id) then
+          break
+        end
+        if before ~= "" then
+          result:insert(pandoc.Str(before))
+        end
+        result:insert(reference(id, labels))
+        rest = after
+      end
+      if #result == 0 then
+        return nil
+      end
+      if rest ~= "" then
+        result:insert(pandoc.Str(rest))
+      end
+      return result
+    end,
+  })
+end
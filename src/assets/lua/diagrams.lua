@@ -0,0 +1,99 @@
+-- Renders ```mermaid, ```dot and ```plantuml code blocks to SVG figures, cached by content hash.
+-- When the rendering tool is missing, the block is left as a source listing and a warning is printed.
+local cache_dir = os.getenv("QUOIN_DIAGRAM_CACHE") or "."
+-- Typst resolves image paths against the .typ file (absolute ones against its project root)
+local typst_dir = os.getenv("QUOIN_TYPST_DIR")
+
+local function read_file(path)
+  local file = assert(io.open(path, "rb"))
+  local data = file:read("a")
+  file:close()
+  return data
+end
+
+local function write_file(path, data)
+  local file = assert(io.open(path, "wb"))
+  file:write(data)
+  file:close()
+end
+
+-- Writes to a temporary file next to `path` and renames it, so a concurrent or interrupted run never
+-- leaves a truncated SVG in the cache
+local function write_file_atomic(path, data)
+  local tmp = string.format("%s.%d-%d.tmp", path, os.time(), math.random(1, 1e9))
+  write_file(tmp, data)
+  local ok, err = os.rename(tmp, path)
+  if not ok then
+    os.remove(tmp)
+    error(err)
+  end
+end
+
+local function file_exists(path)
+  local file = io.open(path, "rb")
+  if file then
+    file:close()
+    return true
+  end
+  return false
+end
+
+local renderers = {
+  dot = function(code)
+    return pandoc.pipe("dot", { "-Tsvg" }, code)
+  end,
+  plantuml = function(code)
+    return pandoc.pipe("plantuml", { "-tsvg", "-pipe" }, code)
+  end,
+  mermaid = function(code)
+    return pandoc.system.with_temporary_directory("quoin-mermaid", function(dir)
+      local input = pandoc.path.join({ dir, "diagram.mmd" })
+      local output = pandoc.path.join({ dir, "diagram.svg" })
+      write_file(input, code)
+      pandoc.pipe("mmdc", { "-q", "-b", "transparent", "-i", input, "-o", output }, "")
+      return read_file(output)
+    end)
+  end,
+}
+renderers.graphviz = renderers.dot
+renderers.puml = renderers.plantuml
+
+function CodeBlock(el)
+  local engine = nil
+  for _, class in ipairs(el.classes) do
+    if renderers[class] then
+      engine = class
+      break
+    end
+  end
+  if not engine then
+    return nil
+  end
+
+  local path = pandoc.path.join({ cache_dir, pandoc.utils.sha1(engine .. "\n" .. el.text) .. ".svg" })
+  if not file_exists(path) then
+    local ok, svg = pcall(renderers[engine], el.text)
+    if not ok then
+      io.stderr:write("[quoin] Warning: could not render " .. engine .. " diagram, showing its source instead ("
+        .. tostring(svg) .. ")\n")
+      return nil
+    end
+    write_file_atomic(path, svg)
+  end
+
+  local image_attributes = {}
+  if el.attributes.width then
+    image_attributes.width = el.attributes.width
+  end
+  if typst_dir then
+    path = pandoc.path.make_relative(path, typst_dir, true)
+  end
+  local image = pandoc.Image({}, path, "", pandoc.Attr("", {}, image_attributes))
+
+  local caption = el.attributes.caption
+  if not caption then
+    return pandoc.Para({ image })
+  end
+  local caption_inlines = pandoc.utils.blocks_to_inlines(pandoc.read(caption, "commonmark").blocks)
+  return pandoc.Figure({ pandoc.Plain({ image }) }, { pandoc.Plain(caption_inlines) }, pandoc.Attr(el.identifier))
+end
@@ -0,0 +1,124 @@
+-- Resolves local images against the resource path (the input's directory first) and rewrites them relative
+-- to the .typ file, since Typst resolves paths against it (absolute ones against its project root).
+-- Images that cannot be found fail the conversion, with the list of them (or a warning with
+-- `--allow-missing-images`, leaving them as written).
+local typst_dir = os.getenv("QUOIN_TYPST_DIR")
+-- Typst's project root for PDF output: images outside it cannot be read
+local typst_root = os.getenv("QUOIN_TYPST_ROOT")
+-- When set, the only directory images may be read from (the web server's)
+local sandbox = os.getenv("QUOIN_IMAGE_SANDBOX")
+-- Where remote images were downloaded to; readable from a sandbox too
+local remote_cache = os.getenv("QUOIN_REMOTE_IMAGE_CACHE")
+local allow_missing = os.getenv("QUOIN_ALLOW_MISSING_IMAGES") == "1"
+
+-- Sources of the images that could not be found, in document order
+local missing = {}
+
+local function warn(message)
+  io.stderr:write("[quoin] Warning: " .. message .. "\n")
+end
+
+local function file_exists(path)
+  local file = io.open(path, "rb")
+  if file then
+    file:close()
+    return true
+  end
+  return false
+end
+
+-- A URL (http, data, ...) rather than a local file; a single letter before the colon is a Windows drive
+local function is_url(src)
+  local scheme = src:match("^([%w+.-]+):")
+  return scheme ~= nil and #scheme > 1
+end
+
+-- Removes "." and ".." components without touching the filesystem
+local function collapse(path)
+  local parts = {}
+  for _, part in ipairs(pandoc.path.split(path)) do
+    if part == ".." then
+      if #parts > 1 then
+        table.remove(parts)
+      end
+    elseif part ~= "." then
+      table.insert(parts, part)
+    end
+  end
+  return pandoc.path.join(parts)
+end
+
+local function inside(path, dir)
+  local prefix = dir:sub(-1) == "/" and dir or dir .. "/"
+  return path == dir or path:sub(1, #prefix) == prefix
+end
+
+-- The absolute path of an existing image, trying the percent-decoded form too (Pandoc escapes spaces)
+local function resolve(src)
+  local candidates = { src }
+  local decoded = src:gsub("%%(%x%x)", function(hex)
+    return string.char(tonumber(hex, 16))
+  end)
+  if decoded ~= src then
+    table.insert(candidates, decoded)
+  end
+  for _, candidate in ipairs(candidates) do
+    if pandoc.path.is_absolute(candidate) then
+      if file_exists(candidate) then
+        return collapse(candidate)
+      end
+    else
+      for _, dir in ipairs(PANDOC_STATE.resource_path) do
+        local path = collapse(pandoc.path.join({ dir, candidate }))
+        if file_exists(path) then
+          return path
+        end
+      end
+    end
+  end
+  return nil
+end
+
+local function Image(el)
+  if el.src == "" or el.src:sub(1, 1) == "#" or is_url(el.src) then
+    return nil
+  end
+  local path = resolve(el.src)
+  if sandbox and not (path and (inside(path, sandbox) or (remote_cache and inside(path, remote_cache)))) then
+    -- Neither the file nor where it was looked for is revealed
+    warn("image '" .. el.src .. "' is not available, showing its description instead")
+    return el.caption
+  end
+  if not path then
+    if not missing[el.src] then
+      missing[el.src] = true
+      table.insert(missing, el.src)
+    end
+    return nil
+  end
+  if typst_root and not inside(path, typst_root) then
+    warn("image '" .. el.src .. "' is outside Typst's project root " .. typst_root
+      .. "; add its directory with --resource-path")
+  end
+  if typst_dir then
+    el.src = pandoc.path.make_relative(path, typst_dir, true)
+  end
+  return el
+end
+
+local function report()
+  if #missing == 0 then
+    return nil
+  end
+  local list = "\n  - " .. table.concat(missing, "\n  - ")
+  if not allow_missing then
+    error("[quoin] " .. #missing .. " image(s) not found in the resource path:" .. list, 0)
+  end
+  warn("leaving " .. #missing .. " missing image(s) as written:" .. list)
+  return nil
+end
+
+return {
+  { Image = Image },
+  { Pandoc = report },
+}
@@ -0,0 +1,16 @@
+-- Puts `::: landscape` and `::: portrait` divs on pages of their own with that orientation, e.g. for a wide table.
+local orientations = { landscape = "true", portrait = "false" }
+
+function Div(el)
+  for _, class in ipairs(el.classes) do
+    local flipped = orientations[class:lower()]
+    if flipped then
+      local result = { pandoc.RawBlock("typst", "#page(flipped: " .. flipped .. ")[") }
+      for _, block in ipairs(el.content) do
+        table.insert(result, block)
+      end
+      table.insert(result, pandoc.RawBlock("typst", "]"))
+      return result
+    end
+  end
+end
@@ -0,0 +1,10 @@
+-- Helpers shared by quoin's filters, loaded with `require("quoin_typst")`. quoin writes this module next to
+-- the filters and puts it on `LUA_PATH`.
+local M = {}
+
+-- A Typst string literal
+function M.string(s)
+  return '"' .. s:gsub('\\', '\\\\'):gsub('"', '\\"') .. '"'
+end
+
+return M
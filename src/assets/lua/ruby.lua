@@ -0,0 +1,59 @@
+-- Ruby (furigana) annotations, drawn by `quoin-ruby` above their base text:
+--   {漢字|かんじ}           one reading for the whole base
+--   {漢字|かん|じ}          one reading per character
+--   [漢字]{.ruby rt="かんじ"}
+local typst_string = require("quoin_typst").string
+
+local function ruby(base, reading)
+  return pandoc.RawInline("typst", "#quoin-ruby(" .. typst_string(base) .. ", " .. typst_string(reading) .. ")")
+end
+
+-- Inlines for `{base|reading}` or `{base|r1|r2|...}`; nil when the text is not a ruby group
+local function group(base, readings)
+  local parts = {}
+  for part in (readings .. "|"):gmatch("(.-)|") do
+    table.insert(parts, part)
+  end
+  local chars = {}
+  for _, code in utf8.codes(base) do
+    table.insert(chars, utf8.char(code))
+  end
+  if #parts > 1 and #parts == #chars then
+    local result = {}
+    for i, char in ipairs(chars) do
+      table.insert(result, ruby(char, parts[i]))
+    end
+    return result
+  end
+  if #parts > 1 then
+    io.stderr:write("[quoin] Warning: ruby {" .. base .. "|" .. readings .. "} has " .. #parts .. " readings for " .. #chars .. " characters\n")
+  end
+  return { ruby(base, table.concat(parts, "")) }
+end
+
+function Str(el)
+  if not el.text:find("{[^{}|]+|[^{}]+}") then
+    return nil
+  end
+  local result = {}
+  local position = 1
+  for start, base, readings, finish in el.text:gmatch("(){([^{}|]+)|([^{}]+)}()") do
+    if start > position then
+      table.insert(result, pandoc.Str(el.text:sub(position, start - 1)))
+    end
+    for _, inline in ipairs(group(base, readings)) do
+      table.insert(result, inline)
+    end
+    position = finish
+  end
+  if position <= #el.text then
+    table.insert(result, pandoc.Str(el.text:sub(position)))
+  end
+  return result
+end
+
+function Span(el)
+  if el.classes:includes("ruby") and el.attributes["rt"] then
+    return ruby(pandoc.utils.stringify(el.content), el.attributes["rt"])
+  end
+end
@@ -0,0 +1,118 @@
+-- Splits the document into slides at H1 and H2 headings and `---` rules: `#quoin-slide(...)[title][body]`.
+-- An H1 with nothing under it becomes a section slide. `::: notes` divs are moved to `#quoin-notes[...]` after
+-- the slide. Lists in `::: incremental` divs (every list with QUOIN_INCREMENTAL=1, except in `::: nonincremental`)
+-- are revealed one item at a time: the slide is repeated once per item, with the later items hidden.
+local incremental_default = os.getenv("QUOIN_INCREMENTAL") == "1"
+
+local function raw(text)
+  return pandoc.RawBlock("typst", text)
+end
+
+local function is_list(block)
+  return block.t == "BulletList" or block.t == "OrderedList"
+end
+
+-- Body blocks without the notes, and the indices of the incremental lists among them
+local function prepare(blocks, notes)
+  local body, lists = pandoc.List(), pandoc.List()
+  local function add(block, incremental)
+    body:insert(block)
+    if incremental and is_list(block) then
+      lists:insert(#body)
+    end
+  end
+  for _, block in ipairs(blocks) do
+    if block.t == "Div" and block.classes:includes("notes") then
+      notes:extend(block.content)
+    elseif block.t == "Div" and (block.classes:includes("incremental") or block.classes:includes("nonincremental")) then
+      for _, inner in ipairs(block.content) do
+        add(inner, block.classes:includes("incremental"))
+      end
+    else
+      add(block, incremental_default)
+    end
+  end
+  return body, lists
+end
+
+-- A copy of the list showing its first `visible` items; the others keep their space
+local function reveal(list, visible)
+  local copy = list:clone()
+  for i, item in ipairs(copy.content) do
+    if i > visible then
+      local hidden = pandoc.List({ raw("#hide[") })
+      hidden:extend(item)
+      hidden:insert(raw("]"))
+      copy.content[i] = hidden
+    end
+  end
+  return copy
+end
+
+local function render(slide, out)
+  local notes = pandoc.List()
+  local body, lists = prepare(slide.body, notes)
+  local kind = (slide.title and slide.title.level == 1 and #body == 0) and "section" or "slide"
+  local steps = 0
+  for _, index in ipairs(lists) do
+    steps = steps + #body[index].content
+  end
+
+  for step = 1, math.max(steps, 1) do
+    local first = step == 1
+    out:insert(raw('#quoin-slide(kind: "' .. kind .. '", first: ' .. tostring(first) .. ")["))
+    if slide.title then
+      local title = slide.title
+      if not first then
+        -- Repeated steps must not duplicate the heading's label
+        title = title:clone()
+        title.identifier = ""
+      end
+      out:insert(title)
+    end
+    out:insert(raw("]["))
+    local shown = step
+    local list_at = {}
+    for _, index in ipairs(lists) do
+      local count = #body[index].content
+      list_at[index] = math.max(math.min(shown, count), 0)
+      shown = shown - count
+    end
+    for i, block in ipairs(body) do
+      out:insert(list_at[i] and reveal(block, list_at[i]) or block)
+    end
+    out:insert(raw("]"))
+  end
+
+  if #notes > 0 then
+    out:insert(raw("#quoin-notes["))
+    out:extend(notes)
+    out:insert(raw("]"))
+  end
+end
+
+function Pandoc(doc)
+  local slides, slide = {}, nil
+  for _, block in ipairs(doc.blocks) do
+    if block.t == "Header" and block.level <= 2 then
+      slide = { title = block, body = pandoc.List() }
+      table.insert(slides, slide)
+    elseif block.t == "HorizontalRule" then
+      slide = { title = nil, body = pandoc.List() }
+      table.insert(slides, slide)
+    else
+      if not slide then
+        slide = { title = nil, body = pandoc.List() }
+        table.insert(slides, slide)
+      end
+      slide.body:insert(block)
+    end
+  end
+
+  local blocks = pandoc.List()
+  for _, s in ipairs(slides) do
+    render(s, blocks)
+  end
+  doc.blocks = blocks
+  return doc
+end
@@ -0,0 +1,163 @@
+-- Replaces `{{name}}` placeholders in the text of the document and its metadata. Code blocks, code spans,
+-- math and raw content are left alone, so template samples (Jinja, `${{ github.sha }}`, ...) survive.
+--
+-- Values come from `-V` and config values first, then from the metadata, where dotted names reach into
+-- nested mappings (`{{author.name}}`). `{{date}}` defaults to today's date (or `SOURCE_DATE_EPOCH`).
+-- `\{{name}}` stays literal: quoin marks these escapes before Pandoc parses the document, since the
+-- reader drops the backslash.
+local strict = os.getenv("QUOIN_STRICT_VARIABLES") == "1"
+local escape_marker = "{\u{E000}{"
+
+local overrides = {}
+for line in (os.getenv("QUOIN_VARIABLES") or ""):gmatch("[^\n]+") do
+  local key, value = line:match("^([^=]+)=(.*)$")
+  if key then
+    overrides[key] = value
+  end
+end
+
+local meta = {}
+local undefined = {}
+
+-- Scalars and lists of scalars as text; mappings have no text
+local function render(value)
+  if type(value) == "boolean" then
+    return tostring(value)
+  end
+  local kind = pandoc.utils.type(value)
+  if kind == "List" then
+    local items = {}
+    for _, item in ipairs(value) do
+      local text = render(item)
+      if text == nil then
+        return nil
+      end
+      table.insert(items, text)
+    end
+    return table.concat(items, ", ")
+  end
+  if kind == "table" or kind == "Meta" then
+    return nil
+  end
+  return pandoc.utils.stringify(value)
+end
+
+local function today()
+  local epoch = tonumber(os.getenv("SOURCE_DATE_EPOCH") or "")
+  return os.date("!%Y-%m-%d", epoch or os.time())
+end
+
+local function lookup(name)
+  if overrides[name] ~= nil then
+    return overrides[name]
+  end
+  local value = meta
+  for part in name:gmatch("[^.]+") do
+    if type(value) ~= "table" then
+      value = nil
+      break
+    end
+    value = value[part]
+    if value == nil then
+      break
+    end
+  end
+  if value ~= nil then
+    return render(value)
+  end
+  if name == "date" then
+    return today()
+  end
+  return nil
+end
+
+local function substitute(text)
+  text = text:gsub("{{%s*([%a_][%w_.%-]*)%s*}}", function(name)
+    local value = lookup(name)
+    if value == nil and not undefined[name] then
+      undefined[name] = true
+      table.insert(undefined, name)
+    end
+    return value
+  end)
+  return (text:gsub(escape_marker, "{{"))
+end
+
+-- Placeholders may contain spaces (`{{ name }}`), so runs of words are substituted as a whole
+local function Inlines(inlines)
+  local result = pandoc.Inlines({})
+  local run, changed = {}, false
+
+  local function flush()
+    if #run == 0 then
+      return
+    end
+    local text = {}
+    for _, inline in ipairs(run) do
+      table.insert(text, inline.t == "Str" and inline.text or " ")
+    end
+    text = table.concat(text)
+    local replaced = substitute(text)
+    if replaced ~= text then
+      result:extend(pandoc.Inlines(replaced))
+      changed = true
+    else
+      result:extend(run)
+    end
+    run = {}
+  end
+
+  for _, inline in ipairs(inlines) do
+    if inline.t == "Str" or inline.t == "Space" then
+      table.insert(run, inline)
+    else
+      flush()
+      result:insert(inline)
+    end
+  end
+  flush()
+  if changed then
+    return result
+  end
+  return nil
+end
+
+-- Escaped placeholders in code and raw content get their backslash back
+local function restore(el)
+  if el.text:find(escape_marker, 1, true) then
+    el.text = el.text:gsub(escape_marker, "\\{{")
+    return el
+  end
+  return nil
+end
+
+local function report()
+  if #undefined == 0 then
+    return nil
+  end
+  local message = #undefined .. " undefined template variable(s): " .. table.concat(undefined, ", ")
+  if strict then
+    error("[quoin] " .. message, 0)
+  end
+  io.stderr:write("[quoin] Warning: leaving " .. message .. " as-is\n")
+  return nil
+end
+
+return {
+  {
+    Meta = function(m)
+      meta = m
+    end,
+  },
+  {
+    Inlines = Inlines,
+    Code = restore,
+    CodeBlock = restore,
+    Math = restore,
+    RawInline = restore,
+    RawBlock = restore,
+  },
+  {
+    Pandoc = report,
+  },
+}
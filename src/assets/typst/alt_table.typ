@@ -1,4 +1,4 @@
-#set table(stroke: 0.5pt + rgb("#888888"), inset: 0.5em)
+#set table(stroke: 0.5pt + quoin-theme.table-stroke, inset: 0.5em)
 #set table(
-  fill: (_, y) => if y == 0 { rgb("#e4e4e4") },
+  fill: (_, y) => if y == 0 { quoin-theme.table-header },
 )
@@ -0,0 +1,28 @@
+#let quoin-callout-styles = (
+  note: (color: quoin-theme.callouts.note, icon: "i"),
+  tip: (color: quoin-theme.callouts.tip, icon: "✓"),
+  important: (color: quoin-theme.callouts.important, icon: "!"),
+  warning: (color: quoin-theme.callouts.warning, icon: "!"),
+  caution: (color: quoin-theme.callouts.caution, icon: "×"),
+)
+
+#let quoin-callout(kind, title: none, body) = {
+  let style = quoin-callout-styles.at(kind, default: quoin-callout-styles.note)
+  let label = if title != none { title } else { quoin-labels.at(kind, default: quoin-labels.note) }
+  let icon = box(baseline: 0.15em, circle(radius: 0.5em, fill: style.color, inset: 0pt,
+    align(center + horizon, text(fill: white, size: 0.75em, weight: "bold", style.icon))))
+
+  block(
+    width: 100%,
+    breakable: true,
+    fill: (quoin-theme.callout-fill)(style.color),
+    // The bar sits where lines start
+    stroke: if quoin-rtl { (right: 3pt + style.color) } else { (left: 3pt + style.color) },
+    radius: 2pt,
+    inset: (x: 1em, y: 0.8em),
+  )[
+    #text(fill: style.color, weight: "bold")[#icon #h(0.2em) #label]
+    #v(0.4em, weak: true)
+    #body
+  ]
+}
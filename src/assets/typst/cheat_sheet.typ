@@ -0,0 +1,28 @@
+// Cheat-sheet sections: each H2 and its content in a box that stays in one column, with the heading in a
+// colored bar. Colors cycle through the theme's callout colors unless the heading sets `color`.
+#let quoin-cheat-colors = (
+  quoin-theme.callouts.note,
+  quoin-theme.callouts.tip,
+  quoin-theme.callouts.important,
+  quoin-theme.callouts.warning,
+  quoin-theme.callouts.caution,
+)
+
+#let quoin-cheat-box(index, color: auto, breakable: false, title, body) = {
+  let color = if color == auto { quoin-cheat-colors.at(calc.rem(index, quoin-cheat-colors.len())) } else { color }
+  block(width: 100%, breakable: breakable, stroke: 0.75pt + color, radius: 2pt, clip: true, below: 0.6em, {
+    block(width: 100%, fill: color, inset: (x: 0.5em, y: 0.3em), below: 0pt, {
+      show heading: set text(fill: white, size: 1.05em)
+      show heading: set block(above: 0pt, below: 0pt)
+      title
+    })
+    block(width: 100%, fill: (quoin-theme.callout-fill)(color), inset: 0.5em, above: 0pt, body)
+  })
+}
+
+// Compact lists and tables
+#set list(spacing: 0.35em, indent: 0.3em, body-indent: 0.4em)
+#set enum(spacing: 0.35em, indent: 0.3em, body-indent: 0.4em)
+#set terms(spacing: 0.35em, hanging-indent: 1em)
+#show table: set table(inset: (x: 0.35em, y: 0.25em))
+#show raw.where(block: true): set text(size: 0.9em)
@@ -0,0 +1,15 @@
+// A title bar across the page: title on the left, subtitle, authors and date on the right.
+#let quoin-title-block(title: none, subtitle: none, authors: (), date: none, abstract-title: none, abstract: none) = {
+  if title == none {
+    return
+  }
+  let color = if quoin-theme.primary != none { quoin-theme.primary } else { quoin-theme.callouts.note }
+  let names = if authors != none { authors.map(author => author.name) } else { () }
+  let details = (subtitle, names.join(", "), date).filter(detail => detail != none and detail != [])
+  block(width: 100%, fill: color, radius: 2pt, inset: (x: 0.8em, y: 0.5em), below: 0.8em, grid(
+    columns: (1fr, auto),
+    align: (start + horizon, end + horizon),
+    text(fill: white, weight: "bold", size: 1.6em, title),
+    if details.len() > 0 { text(fill: white, size: 0.9em, details.join([ #sym.dot.c ])) },
+  ))
+}
@@ -0,0 +1,33 @@
+// Article: a left-aligned title block ending in a rule, the abstract set in from the margins, and the title
+// in the header from the second page.
+#let quoin-title-block(title: none, subtitle: none, authors: (), date: none, abstract-title: none, abstract: none) = {
+  if title != none {
+    block(below: 0.6em, text(size: 1.7em, weight: "bold", fill: quoin-class-color, title))
+  }
+  if subtitle != none {
+    block(below: 0.6em, text(size: 1.2em, subtitle))
+  }
+  if authors != none and authors.len() > 0 {
+    let names = authors.map(author => {
+      author.name
+      if author.at("affiliation", default: none) != none [, #emph(author.affiliation)]
+    })
+    block(below: 0.4em, names.join([ #sym.dot.c ]))
+  }
+  if date != none {
+    block(below: 0.4em, text(size: 0.9em, date))
+  }
+  if abstract != none {
+    block(above: 1.2em, below: 1.2em, inset: (x: 2em), text(size: 0.9em)[*#abstract-title* #h(0.6em) #abstract])
+  }
+  line(length: 100%, stroke: 0.5pt + quoin-class-color)
+}
+
+#set page(header: context {
+  if counter(page).get().first() > 1 and quoin-fields.title != none {
+    set text(size: 0.8em)
+    align(end, emph(quoin-fields.title))
+  }
+})
+#show heading.where(level: 1): set text(size: 1.25em, fill: quoin-class-color)
+#show heading.where(level: 2): set text(size: 1.1em)
@@ -0,0 +1,16 @@
+// Shared by the document classes. `quoin-fields` holds the class's front matter fields as strings, arrays of
+// strings or none.
+#let quoin-class-color = if quoin-theme.primary != none {
+  quoin-theme.primary
+} else if quoin-theme.text != none {
+  quoin-theme.text
+} else {
+  black
+}
+// Multi-line fields such as addresses: one line per array item or per line of the string
+#let quoin-lines(value) = if type(value) == array {
+  value.join(linebreak())
+} else if value != none {
+  value.split("\n").join(linebreak())
+}
+#let quoin-inline(value) = if type(value) == array { value.join(", ") } else { value }
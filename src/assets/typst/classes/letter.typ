@@ -0,0 +1,36 @@
+// Letter: the sender's address at the top right, the recipient's address, the date, an optional subject line,
+// and the closing and signature after the body. Pages are numbered from the second page.
+#let quoin-title-block(title: none, subtitle: none, authors: (), date: none, abstract-title: none, abstract: none) = {
+  align(end, quoin-lines(quoin-fields.sender))
+  v(1.5em)
+  quoin-lines(quoin-fields.recipient)
+  v(1.5em)
+  if date != none {
+    align(end, date)
+  }
+  let subject = if quoin-fields.subject != none { quoin-fields.subject } else { title }
+  if subject != none {
+    v(0.5em)
+    text(weight: "bold", subject)
+  }
+  v(1em)
+}
+
+// The closing (the `closing` label by default) and the signature, or the first line of the sender's address
+#let quoin-letter-closing() = {
+  let sender = quoin-fields.sender
+  let name = if type(sender) == array { sender.first() } else { sender.split("\n").first() }
+  v(1.5em)
+  block(breakable: false, {
+    if quoin-fields.closing != none { quoin-fields.closing } else { quoin-labels.closing }
+    v(3em)
+    if quoin-fields.signature != none { quoin-lines(quoin-fields.signature) } else { name }
+  })
+}
+
+#set page(footer: context {
+  if counter(page).get().first() > 1 {
+    align(center, text(size: 0.8em, quoin-page-of()))
+  }
+})
+#show heading: set text(size: 1em)
@@ -0,0 +1,30 @@
+// Memo: the `memorandum` label (or the title) above a To/From/CC/Date/Subject table and a rule. Pages are
+// numbered from the second page.
+#let quoin-title-block(title: none, subtitle: none, authors: (), date: none, abstract-title: none, abstract: none) = {
+  let name = if title != none { title } else { quoin-labels.memorandum }
+  text(size: 1.6em, weight: "bold", tracking: 0.1em, fill: quoin-class-color, upper(name))
+  v(0.8em)
+  let rows = (
+    (quoin-labels.to, quoin-inline(quoin-fields.to)),
+    (quoin-labels.from, quoin-inline(quoin-fields.from)),
+    (quoin-labels.cc, quoin-inline(quoin-fields.cc)),
+    (quoin-labels.date, date),
+    (quoin-labels.subject, quoin-fields.subject),
+  ).filter(row => row.last() != none)
+  grid(
+    columns: (auto, 1fr),
+    column-gutter: 1.2em,
+    row-gutter: 0.65em,
+    ..rows.map(((label, value)) => (text(weight: "bold", label), value)).flatten(),
+  )
+  v(0.5em)
+  line(length: 100%, stroke: 1pt + quoin-class-color)
+  v(0.8em)
+}
+
+#set page(footer: context {
+  if counter(page).get().first() > 1 {
+    align(center, text(size: 0.8em, quoin-page-of()))
+  }
+})
+#show heading: set text(size: 1.1em)
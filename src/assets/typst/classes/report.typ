@@ -0,0 +1,51 @@
+// Report: a title page, chapters (level 1 headings) starting on a new page, the title in the header and
+// "Page X of Y" in the footer. Pages are numbered from the first page after the title page.
+#let quoin-title-block(title: none, subtitle: none, authors: (), date: none, abstract-title: none, abstract: none) = {
+  if title == none {
+    return
+  }
+  page(header: none, footer: none, align(center + horizon, {
+    text(size: 2.2em, weight: "bold", fill: quoin-class-color, title)
+    if subtitle != none {
+      parbreak()
+      text(size: 1.4em, subtitle)
+    }
+    v(2.5em)
+    if authors != none {
+      for author in authors {
+        block(below: 0.5em, {
+          text(size: 1.1em, author.name)
+          if author.at("affiliation", default: none) != none {
+            linebreak()
+            text(size: 0.9em, author.affiliation)
+          }
+        })
+      }
+    }
+    if date != none {
+      v(1.5em)
+      date
+    }
+    if abstract != none {
+      v(3em)
+      block(width: 80%, align(start)[*#abstract-title* \ #abstract])
+    }
+  }))
+  counter(page).update(1)
+}
+
+#set page(
+  header: context {
+    if counter(page).get().first() > 1 and quoin-fields.title != none {
+      set text(size: 0.8em)
+      align(end, quoin-fields.title)
+      v(-0.6em)
+      line(length: 100%, stroke: 0.5pt + quoin-class-color)
+    }
+  },
+  footer: align(center, text(size: 0.8em, quoin-page-of())),
+)
+#show heading.where(level: 1): it => {
+  pagebreak(weak: true)
+  block(above: 1.5em, below: 1em, text(size: 1.6em, fill: quoin-class-color, it))
+}
@@ -0,0 +1,35 @@
+// Resume: the name (the title) centered above a line of contact details, the abstract as a summary, sections
+// (level 1 headings) in small caps over a rule, and compact lists. Numbered pages only when there are several.
+#let quoin-title-block(title: none, subtitle: none, authors: (), date: none, abstract-title: none, abstract: none) = {
+  align(center, {
+    text(size: 2em, weight: "bold", fill: quoin-class-color, title)
+    if subtitle != none {
+      linebreak()
+      text(size: 1.1em, subtitle)
+    }
+    let contact = (quoin-fields.email, quoin-fields.phone, quoin-fields.location, quoin-fields.website)
+      .filter(item => item != none)
+    if contact.len() > 0 {
+      linebreak()
+      text(size: 0.9em, contact.join([ #h(0.3em) | #h(0.3em) ]))
+    }
+  })
+  if abstract != none {
+    block(above: 1em, abstract)
+  }
+  v(0.3em)
+}
+
+#show heading.where(level: 1): it => block(above: 1.1em, below: 0.6em, {
+  text(size: 1.1em, weight: "bold", fill: quoin-class-color, smallcaps(it.body))
+  v(-0.7em)
+  line(length: 100%, stroke: 0.5pt + quoin-class-color)
+})
+#show heading.where(level: 2): set text(size: 1em)
+#set list(spacing: 0.45em)
+#set enum(spacing: 0.45em)
+#set page(footer: context {
+  if counter(page).final().first() > 1 {
+    align(center, text(size: 0.8em, quoin-page-of()))
+  }
+})
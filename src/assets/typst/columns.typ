@@ -0,0 +1,36 @@
+// Column sections, between the spanning elements. Markers around each section tell the page background
+// where to draw column rules.
+#let quoin-columns(body) = {
+  let (count, gutter) = (quoin-column-layout.count, quoin-column-layout.gutter)
+  [#metadata(none) <quoin-columns-start>]
+  if quoin-column-layout.balance {
+    // A section as tall as its content split evenly, so the last page gets columns of equal height. The slack
+    // leaves room for lines and blocks that cannot be split at the exact midpoint.
+    layout(size => {
+      let width = (size.width - (count - 1) * gutter) / count
+      let height = measure(block(width: width, body)).height
+      block(height: height / count + 2em, breakable: true, columns(count, gutter: gutter, body))
+    })
+  } else {
+    columns(count, gutter: gutter, body)
+  }
+  [#metadata(none) <quoin-columns-end>]
+}
+
+#set page(background: context {
+  let (count, gutter) = (quoin-column-layout.count, quoin-column-layout.gutter)
+  let (margin-x, margin-y) = (quoin-column-layout.margin-x, quoin-column-layout.margin-y)
+  let current = here().page()
+  let column = (100% - 2 * margin-x - (count - 1) * gutter) / count
+  for (start, end) in query(<quoin-columns-start>).zip(query(<quoin-columns-end>)) {
+    let (first, last) = (start.location().position(), end.location().position())
+    if first.page <= current and current <= last.page {
+      let y-start = if first.page == current { first.y } else { margin-y }
+      let y-end = if last.page == current { last.y } else { 100% - margin-y }
+      for i in range(1, count) {
+        place(top + left, dx: margin-x + i * (column + gutter) - gutter / 2, dy: y-start,
+          line(end: (0pt, y-end - y-start), stroke: 0.5pt + quoin-theme.accent))
+      }
+    }
+  }
+}) if quoin-column-layout.rule
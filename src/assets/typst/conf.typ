@@ -0,0 +1,26 @@
+// Pandoc's `conf` sets the page from a paper name and draws its own title block, so it is wrapped: custom page
+// dimensions (`quoin-page`) are set at the very start of the body, and `quoin-title-block` rendered after them.
+#let quoin-pandoc-conf = conf
+#let conf(
+  title: none,
+  subtitle: none,
+  authors: (),
+  date: none,
+  abstract-title: none,
+  abstract: none,
+  ..args,
+) = {
+  set document(title: title) if title != none
+  quoin-pandoc-conf(..args.named(), {
+    set page(..quoin-page)
+    quoin-title-block(
+      title: title,
+      subtitle: subtitle,
+      authors: authors,
+      date: date,
+      abstract-title: abstract-title,
+      abstract: abstract,
+    )
+    args.pos().last()
+  })
+}
@@ -0,0 +1,20 @@
+// Number display equations that carry a label, leave the others unnumbered.
+#set math.equation(numbering: "(1)")
+#show math.equation.where(block: true): it => {
+  if it.has("label") or it.numbering == none {
+    it
+  } else {
+    counter(math.equation).update(n => n - 1)
+    math.equation(it.body, block: true, numbering: none)
+  }
+}
+
+// References to unnumbered headings link to the heading text instead of failing.
+#show ref: it => {
+  let el = it.element
+  if el != none and el.func() == heading and el.numbering == none {
+    link(el.location(), el.body)
+  } else {
+    it
+  }
+}
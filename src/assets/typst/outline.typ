@@ -1,2 +1,5 @@
-#pagebreak(weak: true)
-#outline()
+// Lists of figures, tables and listings, titled with the `list-of-*` labels for the document language.
+#let quoin-list-of(kind, key) = outline(
+  title: quoin-labels.at("list-of-" + key + "s"),
+  target: figure.where(kind: kind),
+)
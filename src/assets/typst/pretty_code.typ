@@ -1,8 +1,47 @@
-#show raw.where(block: false): box.with(fill: luma(240), inset: (x: 3pt), outset: (y: 3pt), radius: 3pt)
+#show raw.where(block: false): box.with(fill: quoin-code-palette.inline, inset: (x: 3pt), outset: (y: 3pt), radius: 3pt)
+
+// Per-block options (linenos, start, highlight, title, wrap) set by the code block filter.
+#let quoin-code-state = state("quoin-code", (:))
+#let quoin-code(..options, body) = {
+  quoin-code-state.update(options.named())
+  body
+  quoin-code-state.update((:))
+}
+
+#show raw.where(block: true): it => context {
+  let palette = quoin-code-palette
+  let options = quoin-code-defaults + quoin-code-state.get()
+  let start = options.at("start", default: 1)
+  let highlight = options.at("highlight", default: ())
+  let title = options.at("title", default: none)
+
+  show raw.line: line => {
+    let number = line.number + start - 1
+    let body = if options.wrap { line.body } else { box(line.body) }
+    let content = if options.linenos {
+      grid(
+        columns: (1.8em, 1fr),
+        column-gutter: 0.8em,
+        align(right, text(fill: palette.line-number, str(number))),
+        body,
+      )
+    } else {
+      body
+    }
+    box(width: 100%, fill: if number in highlight { palette.highlight }, outset: (y: 0.25em), content)
+  }
+  // Long lines may also break after punctuation, not only at spaces
+  show regex("[/._:,;(){}\\[\\]-]"): it => if options.wrap { it + sym.zws } else { it }
 
-#show raw.where(block: true): it => {
   set text(size: 0.9em)
+  set text(fill: palette.foreground) if palette.foreground != none
   align(center)[
-    #block(fill: luma(250), inset: 1em, radius: 5pt, stroke: luma(220), width: 95%)[#align(left)[#it]]
+    #block(fill: palette.background, radius: 5pt, stroke: palette.stroke, width: 95%, clip: true, breakable: true)[
+      #if title != none {
+        block(width: 100%, fill: palette.title-background, inset: (x: 1em, y: 0.5em), below: 0pt,
+          align(left, text(fill: palette.title-foreground, weight: "bold", title)))
+      }
+      #block(width: 100%, inset: 1em, above: 0pt)[#align(left)[#it]]
+    ]
   ]
-}
\ No newline at end of file
+}
@@ -0,0 +1,9 @@
+// Ruby (furigana): the reading set small and centered above its base text, without moving the baseline.
+#let quoin-ruby(base, ruby) = context {
+  let annotation = text(size: 0.5em, ruby)
+  let width = calc.max(measure(base).width, measure(annotation).width)
+  box(width: width, {
+    place(top + center, dy: -0.65em, annotation)
+    align(center, base)
+  })
+}
@@ -0,0 +1,48 @@
+// Slide decks: each `quoin-slide` starts a page and steps the slide number shown in the footer. Repeated steps
+// of an incremental slide keep its number and stay out of the outline and PDF bookmarks.
+#let quoin-slide-counter = counter("quoin-slide")
+#let quoin-slide-color = if quoin-theme.primary != none { quoin-theme.primary } else { quoin-theme.callouts.note }
+
+#set page(footer: context {
+  let number = quoin-slide-counter.get().first()
+  if number > 0 {
+    set text(size: 0.6em)
+    set text(fill: quoin-theme.accent) if quoin-theme.accent != none
+    align(end, [#number / #quoin-slide-counter.final().first()])
+  }
+})
+
+#let quoin-slide(kind: "slide", first: true, title, body) = {
+  pagebreak(weak: true)
+  if first {
+    quoin-slide-counter.step()
+  }
+  set heading(outlined: false, bookmarked: false) if not first
+  if kind == "section" {
+    show heading: it => text(size: 1.6em, weight: "bold", fill: quoin-slide-color, it.body)
+    align(center + horizon, title)
+  } else {
+    {
+      show heading: it => block(below: 0.9em, text(size: 1.3em, weight: "bold", fill: quoin-slide-color, it.body))
+      title
+    }
+    body
+  }
+}
+
+// Speaker notes: a page of their own after the slide, or `<quoin-notes>` metadata for presenter tools
+// (`typst query deck.typ "<quoin-notes>"`).
+#let quoin-notes(body) = {
+  if quoin-slides.speaker-notes {
+    pagebreak(weak: true)
+    set text(size: 0.8em)
+    let side = if quoin-rtl { "right" } else { "left" }
+    block(width: 100%, stroke: ((side): 2pt + quoin-slide-color), inset: ((side): 1em, y: 0.5em), {
+      text(weight: "bold", fill: quoin-slide-color, quoin-labels.notes)
+      parbreak()
+      body
+    })
+  } else {
+    [#metadata(body) <quoin-notes>]
+  }
+}
@@ -0,0 +1,24 @@
+// The title slide: title, subtitle, authors and date centered on a page of their own.
+#let quoin-title-block(title: none, subtitle: none, authors: (), date: none, abstract-title: none, abstract: none) = {
+  if title == none {
+    return
+  }
+  let color = if quoin-theme.primary != none { quoin-theme.primary } else { quoin-theme.callouts.note }
+  let names = if authors != none { authors.map(author => author.name) } else { () }
+  align(center + horizon, {
+    text(size: 2em, weight: "bold", fill: color, title)
+    if subtitle != none {
+      parbreak()
+      text(size: 1.3em, subtitle)
+    }
+    if names.len() > 0 {
+      v(1em)
+      names.join(", ")
+    }
+    if date != none {
+      parbreak()
+      text(size: 0.8em, date)
+    }
+  })
+  pagebreak()
+}
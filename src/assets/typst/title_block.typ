@@ -0,0 +1,33 @@
+#let quoin-title-block(title: none, subtitle: none, authors: (), date: none, abstract-title: none, abstract: none) = {
+  if title != none {
+    align(center, block(inset: 2em)[
+      #text(weight: "bold", size: 1.5em)[#title]
+      #if subtitle != none {
+        parbreak()
+        text(weight: "bold", size: 1.25em)[#subtitle]
+      }
+    ])
+  }
+
+  if authors != none and authors.len() > 0 {
+    grid(
+      columns: (1fr,) * calc.min(authors.len(), 3),
+      row-gutter: 1.5em,
+      ..authors.map(author => align(center)[
+        #author.name \
+        #author.at("affiliation", default: none) \
+        #author.at("email", default: none)
+      ])
+    )
+  }
+
+  if date != none {
+    align(center, block(inset: 1em, date))
+  }
+
+  if abstract != none {
+    block(inset: 2em)[
+      #text(weight: "semibold")[#abstract-title] #h(1em) #abstract
+    ]
+  }
+}
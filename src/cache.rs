@@ -0,0 +1,32 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Returns the directory used to cache rendered or downloaded artifacts of the given kind.
+///
+/// The root is `$QUOIN_CACHE_DIR` if set, otherwise the platform cache directory
+/// (`$XDG_CACHE_HOME/quoin`, `~/.cache/quoin` or `%LOCALAPPDATA%\quoin`), falling back to the system temp dir.
+pub fn cache_dir(kind: &str) -> PathBuf {
+    let root = std::env::var_os("QUOIN_CACHE_DIR")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("XDG_CACHE_HOME").map(|dir| PathBuf::from(dir).join("quoin")))
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join("quoin")))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache").join("quoin")))
+        .unwrap_or_else(|| std::env::temp_dir().join("quoin-cache"));
+    let dir = root.join(kind);
+    std::path::absolute(&dir).unwrap_or(dir)
+}
+
+/// Writes a cache file through a temporary file in the same directory, renamed into place, so concurrent
+/// or interrupted runs never leave a truncated file that later runs would take for a cache hit.
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    // Unique per process and per write, so concurrent conversions in one server never share a temporary file
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}-{}.tmp", std::process::id(), WRITES.fetch_add(1, Ordering::Relaxed)));
+    let tmp = PathBuf::from(tmp);
+    let result = std::fs::write(&tmp, data).and_then(|()| std::fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
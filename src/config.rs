@@ -0,0 +1,252 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::styles::{ColumnLayout, DocumentClass, FontOptions, HeadingStyle, Profile, SlideOptions, Theme, Typography};
+
+/// Name of the configuration file `Config::discover` looks for when `--config` is not given: next to the input
+/// file for `quoin convert` (the working directory when reading stdin, and for `quoin fonts`).
+pub const DEFAULT_CONFIG_FILE: &str = "quoin.toml";
+
+/// Settings that can appear at the top level of a config file or in one of its `[presets.<name>]` tables.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    /// Paper size, e.g. "letter" or "6inx9in".
+    pub paper: Option<String>,
+    /// Landscape orientation.
+    pub landscape: Option<bool>,
+    /// Column count, gutter, rule and balancing.
+    pub columns: Option<ColumnLayout>,
+    /// Cheat-sheet layout with this many columns (3 or 4).
+    pub cheat_sheet: Option<u8>,
+    /// Output format: "document" or "slides".
+    pub format: Option<String>,
+    /// Slide deck options; also selects the slides format.
+    pub slides: Option<SlideOptions>,
+    /// Document class: article, report, letter, memo or resume.
+    pub class: Option<String>,
+    /// Typst files appended to the header includes.
+    pub include_header: Vec<PathBuf>,
+    /// Typst files appended to the after-body includes.
+    pub include_after: Vec<PathBuf>,
+    /// Lua filters run after quoin's own filters.
+    pub lua_filters: Vec<PathBuf>,
+    /// Fonts, e.g. `[fonts]` with `pairing = "technical"` and `mono = ["Iosevka", "DejaVu Sans Mono"]`.
+    pub fonts: Option<FontOptions>,
+    /// Font directories passed to Typst.
+    pub font_paths: Vec<PathBuf>,
+    /// Only use fonts from `font_paths` and those embedded in Typst.
+    pub ignore_system_fonts: Option<bool>,
+    /// Paragraph settings, optionally per density level.
+    pub typography: Option<TypographyConfig>,
+    /// Heading numbering and appearance.
+    pub headings: Option<HeadingStyle>,
+    /// A built-in theme name, a theme file, or an inline `[theme]` table.
+    pub theme: Option<ThemeSetting>,
+    /// Generated strings such as `figure` or `page_of` per language (`[labels.fr]`), replacing the built-in
+    /// ones.
+    pub labels: BTreeMap<String, BTreeMap<String, String>>,
+    /// Ruby (furigana) annotations.
+    pub ruby: Option<bool>,
+    /// Variables, applied like `-V key=value` (before the ones given on the command line).
+    pub variables: BTreeMap<String, toml::Value>,
+}
+
+/// A `quoin.toml` configuration file.
+///
+/// ```toml
+/// include_header = ["brand/header.typ"]
+/// lua_filters = ["filters/glossary.lua"]
+///
+/// [variables]
+/// version = "1.4.0"
+///
+/// [presets.handout]
+/// include_after = ["brand/colophon.typ"]
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    #[serde(flatten)]
+    pub defaults: Preset,
+    pub presets: BTreeMap<String, Preset>,
+}
+
+/// `[typography]` settings for every density level, with `[typography.<level>]` tables
+/// (e.g. `[typography.ultra-dense]`) overriding them for one level.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TypographyConfig {
+    #[serde(flatten)]
+    pub base: Typography,
+    #[serde(rename = "ultra-dense")]
+    pub ultra_dense: Option<Typography>,
+    pub dense: Option<Typography>,
+    pub standard: Option<Typography>,
+    pub comfort: Option<Typography>,
+}
+
+/// `theme = "screen"`, `theme = "brand/theme.toml"` or a `[theme]` table.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ThemeSetting {
+    Named(String),
+    Inline(Box<Theme>),
+}
+
+impl ThemeSetting {
+    pub fn theme(&self) -> Result<Theme> {
+        match self {
+            ThemeSetting::Named(spec) => Theme::resolve(spec),
+            ThemeSetting::Inline(theme) => Ok((**theme).clone()),
+        }
+    }
+}
+
+impl TypographyConfig {
+    /// Settings specific to a density level.
+    pub fn level(&self, density: &str) -> Option<&Typography> {
+        match density {
+            "ultra-dense" => self.ultra_dense.as_ref(),
+            "dense" => self.dense.as_ref(),
+            "comfort" => self.comfort.as_ref(),
+            _ => self.standard.as_ref(),
+        }
+    }
+}
+
+impl Config {
+    /// Parses a config file, resolving the paths it contains against its directory.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read config {}: {}", path.display(), e))?;
+        let mut config: Config =
+            toml::from_str(&content).map_err(|e| anyhow!("Invalid config {}: {}", path.display(), e))?;
+        let dir = std::path::absolute(path)?.parent().map(Path::to_path_buf).unwrap_or_default();
+        config.defaults.resolve_paths(&dir);
+        for preset in config.presets.values_mut() {
+            preset.resolve_paths(&dir);
+        }
+        Ok(config)
+    }
+
+    /// Loads `path` if given, otherwise `quoin.toml` from `dir` when it exists.
+    pub fn discover(path: Option<&Path>, dir: &Path) -> Result<Option<Self>> {
+        match path {
+            Some(path) => Self::load(path).map(Some),
+            None => {
+                let default = dir.join(DEFAULT_CONFIG_FILE);
+                if default.is_file() { Self::load(&default).map(Some) } else { Ok(None) }
+            }
+        }
+    }
+
+    /// Applies the top-level settings, then those of the named preset.
+    pub fn apply(&self, profile: &mut Profile, preset: Option<&str>) -> Result<()> {
+        self.defaults.apply(profile)?;
+        if let Some(name) = preset {
+            let preset = self.presets.get(name).ok_or_else(|| {
+                let known: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+                anyhow!("Unknown preset '{}' (available: {})", name, known.join(", "))
+            })?;
+            preset.apply(profile)?;
+        }
+        Ok(())
+    }
+}
+
+impl Preset {
+    fn resolve_paths(&mut self, dir: &Path) {
+        for path in self
+            .include_header
+            .iter_mut()
+            .chain(&mut self.include_after)
+            .chain(&mut self.lua_filters)
+            .chain(&mut self.font_paths)
+        {
+            *path = dir.join(&*path);
+        }
+        match &mut self.theme {
+            Some(ThemeSetting::Named(spec)) if Theme::named(spec).is_none() => {
+                *spec = dir.join(&*spec).to_string_lossy().into_owned();
+            }
+            Some(ThemeSetting::Inline(theme)) => {
+                if let Some(logo) = &mut theme.logo {
+                    *logo = dir.join(&*logo);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sets the paper, columns, cheat-sheet layout, format and class, appends includes and filters in file order, then sets the theme, labels, ruby, typography, headings, fonts and variables.
+    pub fn apply(&self, profile: &mut Profile) -> Result<()> {
+        if let Some(paper) = &self.paper {
+            profile.set_paper(paper)?;
+        }
+        if let Some(landscape) = self.landscape {
+            profile.set_landscape(landscape);
+        }
+        if let Some(columns) = &self.columns {
+            profile.set_columns(columns)?;
+        }
+        if let Some(columns) = self.cheat_sheet {
+            profile.set_cheat_sheet(columns)?;
+        }
+        if let Some(format) = &self.format {
+            profile.set_format(format)?;
+        }
+        if let Some(slides) = &self.slides {
+            profile.set_slides(slides)?;
+        }
+        if let Some(class) = &self.class {
+            profile.set_class(DocumentClass::parse(class)?);
+        }
+        for path in &self.include_header {
+            profile.add_header_include_file(path)?;
+        }
+        for path in &self.include_after {
+            profile.add_after_body_include_file(path)?;
+        }
+        for path in &self.lua_filters {
+            profile.add_lua_filter_file(path)?;
+        }
+        for path in &self.font_paths {
+            profile.add_font_path(path)?;
+        }
+        if let Some(ignore) = self.ignore_system_fonts {
+            profile.set_ignore_system_fonts(ignore);
+        }
+        if let Some(theme) = &self.theme {
+            profile.set_theme(&theme.theme()?)?;
+        }
+        for (lang, labels) in &self.labels {
+            profile.set_labels(lang, labels)?;
+        }
+        if self.ruby == Some(true) {
+            profile.set_ruby();
+        }
+        if let Some(typography) = &self.typography {
+            profile.set_typography(&typography.base)?;
+            if let Some(level) = typography.level(&profile.density) {
+                profile.set_typography(level)?;
+            }
+        }
+        if let Some(headings) = &self.headings {
+            profile.set_heading_style(headings)?;
+        }
+        if let Some(fonts) = &self.fonts {
+            profile.set_fonts(fonts)?;
+        }
+        for (key, value) in &self.variables {
+            let value = match value {
+                toml::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            profile.override_variable(key, &value);
+        }
+        Ok(())
+    }
+}
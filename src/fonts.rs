@@ -0,0 +1,332 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::debug;
+use which::which;
+
+use crate::styles::FontOptions;
+
+/// A font family visible to the Typst engine.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FontFamily {
+    pub name: String,
+    pub variants: Vec<FontVariant>,
+    /// Scripts the family has glyphs for (e.g. "Latin", "CJK", "Arabic"). Empty when unknown.
+    pub scripts: Vec<String>,
+    /// Where Typst finds the family: "system", "font-path" or "typst" (built into Typst).
+    pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct FontVariant {
+    /// "normal", "italic" or "oblique".
+    pub style: String,
+    /// 100 (thin) to 900 (black).
+    pub weight: u16,
+    /// Width in percent of normal, e.g. 100 or 87.5 for semi-condensed.
+    pub stretch: String,
+}
+
+impl std::fmt::Display for FontVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.style, self.weight)?;
+        if self.stretch != "100" {
+            write!(f, " ({}% width)", self.stretch)?;
+        }
+        Ok(())
+    }
+}
+
+/// Widths in percent of normal, by OpenType width class (1-9).
+const WIDTH_PERCENT: [&str; 9] = ["50", "62.5", "75", "87.5", "100", "112.5", "125", "150", "200"];
+
+/// A sample character per script, used to hint at coverage.
+const SCRIPT_SAMPLES: &[(&str, char)] = &[
+    ("Latin", 'é'),
+    ("Greek", 'Ω'),
+    ("Cyrillic", 'Ж'),
+    ("Arabic", 'ب'),
+    ("Hebrew", 'א'),
+    ("Devanagari", 'क'),
+    ("CJK", '中'),
+    ("Japanese kana", 'あ'),
+    ("Korean Hangul", '한'),
+];
+
+/// Serif (Song, Ming, Mincho, Myeongjo) and sans (Hei, Gothic, Dotum) families for a CJK language, most
+/// preferred first. Traditional Chinese ("zh-TW", "zh-HK", "zh-Hant") has its own. `None` for other languages.
+pub fn cjk_families(lang: &str) -> Option<(&'static [&'static str], &'static [&'static str])> {
+    let lang = lang.to_lowercase().replace('_', "-");
+    let traditional = ["-tw", "-hk", "-mo", "-hant"].iter().any(|tag| lang.contains(tag));
+    match crate::labels::primary(&lang).as_str() {
+        "zh" if traditional => Some((
+            &["Noto Serif CJK TC", "Source Han Serif TC", "Songti TC", "PMingLiU"],
+            &["Noto Sans CJK TC", "Source Han Sans TC", "PingFang TC", "Microsoft JhengHei"],
+        )),
+        "zh" => Some((
+            &["Noto Serif CJK SC", "Source Han Serif SC", "Songti SC", "SimSun"],
+            &["Noto Sans CJK SC", "Source Han Sans SC", "PingFang SC", "Microsoft YaHei", "WenQuanYi Micro Hei"],
+        )),
+        "ja" => Some((
+            &["Noto Serif CJK JP", "Source Han Serif", "Hiragino Mincho ProN", "Yu Mincho", "IPAexMincho"],
+            &["Noto Sans CJK JP", "Source Han Sans", "Hiragino Sans", "Yu Gothic", "Meiryo", "IPAexGothic"],
+        )),
+        "ko" => Some((
+            &["Noto Serif CJK KR", "Source Han Serif K", "AppleMyungjo", "Nanum Myeongjo", "Batang"],
+            &["Noto Sans CJK KR", "Source Han Sans K", "Apple SD Gothic Neo", "Malgun Gothic", "NanumGothic"],
+        )),
+        _ => None,
+    }
+}
+
+/// Warns when a Chinese, Japanese or Korean document has no font with glyphs for its script: Typst would
+/// draw boxes ("tofu") instead of the text.
+pub fn cjk_font_warning(lang: &str, available: &[FontFamily]) -> Option<String> {
+    let script = match crate::labels::primary(lang).as_str() {
+        "zh" => "CJK",
+        "ja" => "Japanese kana",
+        "ko" => "Korean Hangul",
+        _ => return None,
+    };
+    if available.iter().any(|family| family.scripts.iter().any(|s| s == script)) {
+        return None;
+    }
+    let (_, sans) = cjk_families(lang)?;
+    Some(format!(
+        "no font with {} glyphs is available to Typst for lang '{}', so the text will render as boxes; \
+         install one such as '{}' or pass its directory with --font-path",
+        script, lang, sans[0]
+    ))
+}
+
+/// Lists the font families Typst can see: fonts in `font_paths`, system fonts unless `ignore_system_fonts`
+/// is set, and the fonts built into Typst (when the `typst` binary is available), sorted by name.
+pub fn list_fonts(font_paths: &[PathBuf], ignore_system_fonts: bool) -> Vec<FontFamily> {
+    let mut db = fontdb::Database::new();
+    if !ignore_system_fonts {
+        db.load_system_fonts();
+    }
+    for path in font_paths {
+        db.load_fonts_dir(path);
+    }
+
+    let mut families: Vec<FontFamily> = Vec::new();
+    for face in db.faces() {
+        let Some((name, _)) = face.families.first() else {
+            continue;
+        };
+        let variant = FontVariant {
+            style: match face.style {
+                fontdb::Style::Normal => "normal",
+                fontdb::Style::Italic => "italic",
+                fontdb::Style::Oblique => "oblique",
+            }
+            .to_string(),
+            weight: face.weight.0,
+            stretch: WIDTH_PERCENT[usize::from(face.stretch.to_number().clamp(1, 9)) - 1].to_string(),
+        };
+        match families.iter_mut().find(|f| f.name == *name) {
+            Some(family) => {
+                if !family.variants.contains(&variant) {
+                    family.variants.push(variant);
+                }
+            }
+            None => {
+                let in_font_path = match &face.source {
+                    fontdb::Source::File(path) | fontdb::Source::SharedFile(path, _) => {
+                        font_paths.iter().any(|dir| path.starts_with(dir))
+                    }
+                    fontdb::Source::Binary(_) => false,
+                };
+                let scripts = db.with_face_data(face.id, scripts).unwrap_or_default();
+                families.push(FontFamily {
+                    name: name.clone(),
+                    variants: vec![variant],
+                    scripts,
+                    source: if in_font_path { "font-path" } else { "system" }.to_string(),
+                });
+            }
+        }
+    }
+
+    // Typst also ships a few fonts of its own
+    match typst_fonts(font_paths, ignore_system_fonts) {
+        Ok(typst_families) => {
+            for family in typst_families {
+                if !families.iter().any(|f| f.name.eq_ignore_ascii_case(&family.name)) {
+                    families.push(family);
+                }
+            }
+        }
+        Err(e) => debug!("Not listing Typst's built-in fonts: {}", e),
+    }
+
+    for family in &mut families {
+        family.variants.sort();
+    }
+    families.sort_by_key(|family| family.name.to_lowercase());
+    families
+}
+
+/// Like [`list_fonts`], but scanned once per process for each set of font paths. Conversions check fonts on
+/// every run (every request on the server), and fonts rarely change while quoin is running.
+pub fn cached_fonts(font_paths: &[PathBuf], ignore_system_fonts: bool) -> Arc<Vec<FontFamily>> {
+    type Cache = Mutex<HashMap<(Vec<PathBuf>, bool), Arc<Vec<FontFamily>>>>;
+    static CACHE: OnceLock<Cache> = OnceLock::new();
+
+    let key = (font_paths.to_vec(), ignore_system_fonts);
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(families) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return families.clone();
+    }
+    // Scanned without holding the lock; a concurrent scan of the same key just does the work twice
+    let families = Arc::new(list_fonts(font_paths, ignore_system_fonts));
+    cache.lock().unwrap_or_else(|e| e.into_inner()).insert(key, families.clone());
+    families
+}
+
+fn scripts(data: &[u8], index: u32) -> Vec<String> {
+    let Ok(face) = ttf_parser::Face::parse(data, index) else {
+        return Vec::new();
+    };
+    SCRIPT_SAMPLES
+        .iter()
+        .filter(|(_, sample)| face.glyph_index(*sample).is_some())
+        .map(|(script, _)| script.to_string())
+        .collect()
+}
+
+/// Lists the font families reported by `typst fonts --variants`.
+pub fn typst_fonts(font_paths: &[PathBuf], ignore_system_fonts: bool) -> Result<Vec<FontFamily>> {
+    if which("typst").is_err() {
+        return Err(anyhow!("Typst not found in system PATH"));
+    }
+    let mut cmd = Command::new("typst");
+    cmd.arg("fonts").arg("--variants");
+    for path in font_paths {
+        cmd.arg("--font-path").arg(path);
+    }
+    if ignore_system_fonts {
+        cmd.arg("--ignore-system-fonts");
+    }
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(anyhow!("typst fonts failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(parse_typst_fonts(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses `typst fonts --variants` output: a family name per line, followed by `- Style: Normal, Weight: 400,
+/// Stretch: 100%` variant lines.
+pub fn parse_typst_fonts(output: &str) -> Vec<FontFamily> {
+    let mut families: Vec<FontFamily> = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        match (trimmed.strip_prefix("- "), families.last_mut()) {
+            (Some(description), Some(family)) => family.variants.push(parse_variant(description)),
+            _ => families.push(FontFamily {
+                name: trimmed.to_string(),
+                variants: Vec::new(),
+                scripts: Vec::new(),
+                source: "typst".to_string(),
+            }),
+        }
+    }
+    families
+}
+
+fn parse_variant(description: &str) -> FontVariant {
+    let mut variant = FontVariant { style: "normal".to_string(), weight: 400, stretch: "100".to_string() };
+    for field in description.split(", ") {
+        match field.split_once(": ") {
+            Some(("Style", style)) => variant.style = style.to_lowercase(),
+            Some(("Weight", weight)) => variant.weight = weight.parse().unwrap_or(400),
+            Some(("Stretch", stretch)) => {
+                // "100%" in recent versions, "FontStretch(1000)" in older ones
+                variant.stretch = match stretch.strip_prefix("FontStretch(").and_then(|s| s.strip_suffix(')')) {
+                    Some(permille) => permille.parse::<f32>().map(|p| format!("{}", p / 10.0)).unwrap_or_default(),
+                    None => stretch.trim_end_matches('%').to_string(),
+                }
+            }
+            _ => {}
+        }
+    }
+    variant
+}
+
+/// Describes the configured fonts Typst cannot find, so substitutions are not silent.
+pub fn missing_font_warnings(fonts: &FontOptions, available: &[FontFamily]) -> Vec<String> {
+    let is_available = |name: &str| available.iter().any(|f| f.name.eq_ignore_ascii_case(name));
+    let mut warnings = Vec::new();
+    for (role, families) in fonts.roles() {
+        if families.is_empty() {
+            continue;
+        }
+        match families.iter().position(|f| is_available(f)) {
+            Some(0) => {}
+            Some(index) => {
+                let missing: Vec<String> = families[..index].iter().map(|f| format!("'{}'", f)).collect();
+                warnings.push(format!(
+                    "{} font(s) {} not available to Typst; using '{}'",
+                    role,
+                    missing.join(", "),
+                    families[index]
+                ));
+            }
+            None => warnings.push(format!(
+                "none of the {} fonts ({}) are available to Typst; it will substitute its default font",
+                role,
+                families.join(", ")
+            )),
+        }
+    }
+    warnings
+}
+
+#[cfg(feature = "embedded-fonts")]
+#[derive(rust_embed::RustEmbed)]
+#[folder = "fonts/"]
+struct EmbeddedFonts;
+
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "otc"];
+
+/// Unpacks the fonts compiled into the binary (`embedded-fonts` feature) into the cache and returns their directory.
+///
+/// Returns `None` when the feature is disabled or no fonts were embedded.
+pub fn embedded_font_dir() -> Result<Option<PathBuf>> {
+    #[cfg(feature = "embedded-fonts")]
+    {
+        let dir = crate::cache::cache_dir("fonts");
+        let mut found = false;
+        for name in EmbeddedFonts::iter() {
+            let Some(file) = EmbeddedFonts::get(&name).filter(|_| is_font_file(Path::new(name.as_ref()))) else {
+                continue;
+            };
+            found = true;
+            let path = dir.join(name.as_ref());
+            if std::fs::metadata(&path).map(|m| m.len()).ok() != Some(file.data.len() as u64) {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, &file.data)?;
+            }
+        }
+        if found {
+            return Ok(Some(dir));
+        }
+    }
+    Ok(None)
+}
+
+/// Whether `path` looks like a font file Typst can load.
+pub fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| FONT_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
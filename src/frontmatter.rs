@@ -0,0 +1,24 @@
+use serde_yaml::{Mapping, Value};
+
+/// Extracts the YAML front matter block (`---` ... `---` or `...`) at the top of a Markdown document.
+///
+/// Returns `None` when there is no front matter or it is not a YAML mapping.
+pub fn parse(markdown: &str) -> Option<Mapping> {
+    let mut lines = markdown.lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+    let mut yaml = String::new();
+    for line in lines {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return match serde_yaml::from_str(&yaml) {
+                Ok(Value::Mapping(mapping)) => Some(mapping),
+                _ => None,
+            };
+        }
+        yaml.push_str(line);
+        yaml.push('\n');
+    }
+    None
+}
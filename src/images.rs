@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+/// An image reference found in Markdown source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageRef {
+    /// Byte range of the image destination (URL or path) in the source.
+    pub range: Range<usize>,
+    /// The destination as written.
+    pub url: String,
+}
+
+/// Finds image destinations in Markdown: inline images (`![alt](path "title")`), reference-style
+/// image definitions (`[id]: path`) and HTML `<img src="...">` tags. Code blocks and code spans are skipped.
+pub fn find_images(markdown: &str) -> Vec<ImageRef> {
+    let mut images = Vec::new();
+    let mut reference_labels = HashSet::new();
+    let mut definitions = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    let mut offset = 0;
+
+    for line in markdown.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+
+        // Fenced code blocks
+        let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+        if let Some(c) = fence_char.filter(|_| indent <= 3) {
+            let run = trimmed.chars().take_while(|x| *x == c).count();
+            if run >= 3 {
+                match fence {
+                    None => {
+                        fence = Some((c, run));
+                        continue;
+                    }
+                    Some((open, len)) if open == c && run >= len && trimmed[run..].trim().is_empty() => {
+                        fence = None;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if fence.is_some() {
+            continue;
+        }
+
+        // Reference definitions: `[label]: destination`
+        if indent <= 3
+            && let Some((label, range)) = parse_definition(trimmed)
+        {
+            let start = line_start + indent;
+            definitions.push((label, start + range.start..start + range.end));
+            continue;
+        }
+
+        scan_inline(line, line_start, &mut images, &mut reference_labels);
+    }
+
+    for (label, range) in definitions {
+        if reference_labels.contains(&label) {
+            images.push(ImageRef { url: markdown[range.clone()].to_string(), range });
+        }
+    }
+    images.sort_by_key(|image| image.range.start);
+    images
+}
+
+/// Scans one line for inline images, image references and `<img>` tags.
+fn scan_inline(line: &str, line_start: usize, images: &mut Vec<ImageRef>, labels: &mut HashSet<String>) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => {
+                // Skip the code span, if it is closed on this line
+                let run = bytes[i..].iter().take_while(|b| **b == b'`').count();
+                let closing = "`".repeat(run);
+                i = match line[i + run..].find(&closing) {
+                    Some(end) => i + run + end + run,
+                    None => i + run,
+                };
+            }
+            b'!' if bytes.get(i + 1) == Some(&b'[') => match matching_bracket(line, i + 1) {
+                Some(close) => {
+                    let alt = &line[i + 2..close];
+                    let after = close + 1;
+                    if bytes.get(after) == Some(&b'(') {
+                        if let Some((range, end)) = parse_destination(line, after + 1) {
+                            images.push(ImageRef {
+                                url: line[range.clone()].to_string(),
+                                range: line_start + range.start..line_start + range.end,
+                            });
+                            i = end;
+                            continue;
+                        }
+                    } else if bytes.get(after) == Some(&b'[') {
+                        if let Some(label_end) = line[after + 1..].find(']') {
+                            let label = &line[after + 1..after + 1 + label_end];
+                            labels.insert(normalize_label(if label.is_empty() { alt } else { label }));
+                            i = after + label_end + 2;
+                            continue;
+                        }
+                    } else {
+                        labels.insert(normalize_label(alt));
+                    }
+                    i = after;
+                }
+                None => i += 2,
+            },
+            b'<' if bytes.len() > i + 4 && bytes[i + 1..i + 4].eq_ignore_ascii_case(b"img") => {
+                let tag_end = line[i..].find('>').map(|e| i + e).unwrap_or(line.len());
+                if let Some(range) = html_src(&line[i..tag_end]) {
+                    let range = i + range.start..i + range.end;
+                    images.push(ImageRef {
+                        url: line[range.clone()].to_string(),
+                        range: line_start + range.start..line_start + range.end,
+                    });
+                }
+                i = tag_end;
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// Index of the `]` matching the `[` at `open`, honoring nesting and escapes.
+fn matching_bracket(line: &str, open: usize) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Parses a link destination starting at `start` (just after `(`).
+/// Returns the destination range and the index just past the closing `)`.
+fn parse_destination(line: &str, start: usize) -> Option<(Range<usize>, usize)> {
+    let bytes = line.as_bytes();
+    let mut i = start;
+    while i < bytes.len() && bytes[i] == b' ' {
+        i += 1;
+    }
+    let range = if bytes.get(i) == Some(&b'<') {
+        let end = i + 1 + line[i + 1..].find('>')?;
+        let range = i + 1..end;
+        i = end + 1;
+        range
+    } else {
+        let begin = i;
+        let mut depth = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 1,
+                b'(' => depth += 1,
+                b')' if depth == 0 => break,
+                b')' => depth -= 1,
+                b' ' | b'\t' | b'\n' | b'\r' => break,
+                _ => {}
+            }
+            i += 1;
+        }
+        begin..i.min(bytes.len())
+    };
+    // Skip an optional title up to the closing parenthesis
+    let close = i + line[i..].find(')')?;
+    if range.is_empty() {
+        return None;
+    }
+    Some((range, close + 1))
+}
+
+/// Parses `[label]: destination`, returning the normalized label and the destination range.
+fn parse_definition(line: &str) -> Option<(String, Range<usize>)> {
+    if !line.starts_with('[') || line.starts_with("[^") {
+        return None;
+    }
+    let close = line.find("]:")?;
+    let label = &line[1..close];
+    let rest = &line[close + 2..];
+    let leading = rest.len() - rest.trim_start().len();
+    let start = close + 2 + leading;
+    let dest = &line[start..];
+    let (begin, len) = if let Some(stripped) = dest.strip_prefix('<') {
+        (start + 1, stripped.find('>')?)
+    } else {
+        (start, dest.find(char::is_whitespace).unwrap_or(dest.len()))
+    };
+    if len == 0 {
+        return None;
+    }
+    Some((normalize_label(label), begin..begin + len))
+}
+
+/// Finds the value of the `src` attribute in an `<img ...` tag.
+fn html_src(tag: &str) -> Option<Range<usize>> {
+    let lower = tag.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(pos) = lower[search..].find("src") {
+        let at = search + pos;
+        search = at + 3;
+        let preceded_by_space = lower[..at].ends_with(|c: char| c.is_whitespace());
+        let rest = lower[search..].trim_start();
+        if !preceded_by_space || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = tag.len() - rest.len() + 1;
+        let value = tag[value_start..].trim_start();
+        let value_start = tag.len() - value.len();
+        let quote = value.chars().next()?;
+        return if quote == '"' || quote == '\'' {
+            let end = value[1..].find(quote)?;
+            Some(value_start + 1..value_start + 1 + end)
+        } else {
+            let end = value.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(value.len());
+            Some(value_start..value_start + end)
+        };
+    }
+    None
+}
+
+fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Whether an image destination points to a URL (http, data, ...) rather than a local file.
+pub fn is_url(url: &str) -> bool {
+    match url.split_once(':') {
+        // A single letter before the colon is a Windows drive, not a scheme
+        Some((scheme, _)) if scheme.len() > 1 => {
+            scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+        }
+        _ => false,
+    }
+}
+
+/// Replaces image destinations for which `rewrite` returns a new value.
+/// Destinations containing spaces are wrapped in angle brackets.
+pub fn rewrite_images(markdown: &str, mut rewrite: impl FnMut(&ImageRef) -> Option<String>) -> String {
+    let mut result = String::with_capacity(markdown.len());
+    let mut last = 0;
+    for image in find_images(markdown) {
+        if let Some(new_url) = rewrite(&image) {
+            let bracketed = markdown[..image.range.start].ends_with('<');
+            result.push_str(&markdown[last..image.range.start]);
+            if new_url.contains(' ') && !bracketed && !markdown[..image.range.start].ends_with(['"', '\'']) {
+                result.push_str(&format!("<{}>", new_url));
+            } else {
+                result.push_str(&new_url);
+            }
+            last = image.range.end;
+        }
+    }
+    result.push_str(&markdown[last..]);
+    result
+}
+
+/// Removes `.` and `..` components without touching the filesystem.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// Expresses the absolute `path` relative to the absolute directory `base`.
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return path.iter().collect();
+    }
+    let mut result = PathBuf::new();
+    for _ in common..base.len() {
+        result.push("..");
+    }
+    for component in &path[common..] {
+        result.push(component);
+    }
+    result
+}
@@ -0,0 +1,233 @@
+use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+
+use crate::images;
+
+/// Markdown with its include directives expanded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expanded {
+    pub markdown: String,
+    /// Every file pulled in, directly or transitively, in the order they were first included (logged by `convert`).
+    pub files: Vec<PathBuf>,
+}
+
+/// Expands include directives, resolving paths relative to the including file:
+///
+/// * `!include chapter2.md` on its own line inlines another Markdown file (recursively, without its front matter).
+/// * A fenced code block with an `include` attribute, e.g. ```` ```{.rust include="src/lib.rs" lines="10-40"} ````,
+///   takes its content from a file, optionally restricted to a line range.
+///
+/// Include cycles are reported as errors; `source`, the file `markdown` was read from (if any), counts as part
+/// of the cycle. Relative images in included Markdown are rebased so they still point next to the file that
+/// references them.
+pub fn expand_includes(markdown: &str, base_dir: &Path, source: Option<&Path>) -> Result<Expanded> {
+    let mut files = Vec::new();
+    let mut stack = match source {
+        Some(source) => vec![images::normalize(&std::path::absolute(source)?)],
+        None => Vec::new(),
+    };
+    let markdown = expand(markdown, base_dir, &mut stack, &mut files)?;
+    Ok(Expanded { markdown, files })
+}
+
+fn expand(markdown: &str, base_dir: &Path, stack: &mut Vec<PathBuf>, files: &mut Vec<PathBuf>) -> Result<String> {
+    let mut result = String::with_capacity(markdown.len());
+    let mut fence: Option<(char, usize)> = None;
+    let mut lines = markdown.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+        let fence_char = trimmed.chars().next().filter(|c| (*c == '`' || *c == '~') && indent <= 3);
+        let run = fence_char.map(|c| trimmed.chars().take_while(|x| *x == c).count()).unwrap_or(0);
+
+        if let Some((open, len)) = fence {
+            if fence_char == Some(open) && run >= len && trimmed[run..].trim().is_empty() {
+                fence = None;
+            }
+            result.push_str(line);
+            continue;
+        }
+
+        if let Some(c) = fence_char.filter(|_| run >= 3) {
+            let info = trimmed[run..].trim();
+            match attribute(info, "include") {
+                Some(path) => {
+                    // Skip the placeholder body up to the closing fence
+                    for body_line in lines.by_ref() {
+                        let body = body_line.trim_start_matches(' ');
+                        if body.starts_with(&c.to_string().repeat(run)) && body.trim_start_matches(c).trim().is_empty() {
+                            break;
+                        }
+                    }
+                    result.push_str(&include_code(&line[..indent], c, run, info, &path, base_dir, files)?);
+                }
+                None => {
+                    fence = Some((c, run));
+                    result.push_str(line);
+                }
+            }
+            continue;
+        }
+
+        if let Some(path) = trimmed.strip_prefix("!include ").map(str::trim).filter(|p| !p.is_empty() && indent <= 3) {
+            let path = resolve(base_dir, unquote(path));
+            let included = include_markdown(&path, stack, files)?;
+            result.push_str(&included);
+            if !included.ends_with('\n') {
+                result.push('\n');
+            }
+            continue;
+        }
+
+        result.push_str(line);
+    }
+    Ok(result)
+}
+
+fn include_markdown(path: &Path, stack: &mut Vec<PathBuf>, files: &mut Vec<PathBuf>) -> Result<String> {
+    if stack.contains(&path.to_path_buf()) {
+        let mut cycle: Vec<String> = stack.iter().map(|p| p.display().to_string()).collect();
+        cycle.push(path.display().to_string());
+        return Err(anyhow!("Include cycle detected: {}", cycle.join(" -> ")));
+    }
+    let content = read_included(path, stack)?;
+    if !files.contains(&path.to_path_buf()) {
+        files.push(path.to_path_buf());
+    }
+
+    let base_dir = path.parent().unwrap_or(Path::new("/"));
+    let content = strip_front_matter(&content);
+    // Images are resolved against the top-level document later, so make them absolute here
+    let content = images::rewrite_images(content, |image| {
+        if images::is_url(&image.url) || Path::new(&image.url).is_absolute() || image.url.starts_with('#') {
+            return None;
+        }
+        Some(resolve(base_dir, &image.url).to_string_lossy().replace('\\', "/"))
+    });
+
+    stack.push(path.to_path_buf());
+    let expanded = expand(&content, base_dir, stack, files);
+    stack.pop();
+    expanded
+}
+
+fn include_code(
+    indent: &str,
+    fence_char: char,
+    run: usize,
+    info: &str,
+    path: &str,
+    base_dir: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<String> {
+    let path = resolve(base_dir, path);
+    let content = read_included(&path, &[])?;
+    if !files.contains(&path) {
+        files.push(path.clone());
+    }
+
+    let all_lines: Vec<&str> = content.lines().collect();
+    let (start, end) = match attribute(info, "lines") {
+        Some(range) => parse_range(&range, all_lines.len())
+            .ok_or_else(|| anyhow!("Invalid line range '{}' for included file {}", range, path.display()))?,
+        None => (1, all_lines.len()),
+    };
+    if end > all_lines.len() {
+        return Err(anyhow!(
+            "Line range '{}' is past the end of included file {} ({} lines)",
+            attribute(info, "lines").unwrap_or_default(),
+            path.display(),
+            all_lines.len()
+        ));
+    }
+    let selected = &all_lines[start - 1..end];
+
+    // Number lines as in the source file unless the block says otherwise
+    let mut info = info.to_string();
+    if start > 1 && attribute(&info, "startFrom").is_none() && info.ends_with('}') {
+        info.insert_str(info.len() - 1, &format!(" startFrom={}", start));
+    }
+
+    // The fence must be longer than any fence inside the included code
+    let longest = selected
+        .iter()
+        .map(|line| line.trim_start().chars().take_while(|c| *c == fence_char).count())
+        .max()
+        .unwrap_or(0);
+    let fence = fence_char.to_string().repeat(run.max(longest + 1));
+
+    let mut block = format!("{}{}{}\n", indent, fence, info);
+    for line in selected {
+        block.push_str(line);
+        block.push('\n');
+    }
+    block.push_str(&format!("{}{}\n", indent, fence));
+    Ok(block)
+}
+
+fn read_included(path: &Path, stack: &[PathBuf]) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| match stack.last() {
+        Some(parent) => anyhow!("Failed to include {} (from {}): {}", path.display(), parent.display(), e),
+        None => anyhow!("Failed to include {}: {}", path.display(), e),
+    })
+}
+
+fn resolve(base_dir: &Path, path: &str) -> PathBuf {
+    images::normalize(&base_dir.join(path))
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches(|c| c == '"' || c == '\'')
+}
+
+fn strip_front_matter(markdown: &str) -> &str {
+    if crate::frontmatter::parse(markdown).is_none() {
+        return markdown;
+    }
+    let mut offset = 0;
+    for (i, line) in markdown.split_inclusive('\n').enumerate() {
+        offset += line.len();
+        let trimmed = line.trim_end();
+        if i > 0 && (trimmed == "---" || trimmed == "...") {
+            return &markdown[offset..];
+        }
+    }
+    markdown
+}
+
+/// Reads `key="value"`, `key='value'` or `key=value` from a code block info string.
+fn attribute(info: &str, key: &str) -> Option<String> {
+    let mut search = 0;
+    while let Some(pos) = info[search..].find(key) {
+        let at = search + pos;
+        search = at + key.len();
+        let standalone = at == 0 || info[..at].ends_with([' ', '{', '\t']);
+        let Some(rest) = info[search..].strip_prefix('=') else {
+            continue;
+        };
+        if !standalone {
+            continue;
+        }
+        return match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].find(quote).map(|end| rest[1..1 + end].to_string()),
+            _ => Some(rest.split([' ', '}', '\t']).next().unwrap_or_default().to_string()),
+        };
+    }
+    None
+}
+
+/// Parses "10-40", "10-", "-5" or "12" into an inclusive 1-based range.
+fn parse_range(spec: &str, line_count: usize) -> Option<(usize, usize)> {
+    let (start, end) = match spec.split_once('-') {
+        Some((start, end)) => (
+            if start.trim().is_empty() { 1 } else { start.trim().parse().ok()? },
+            if end.trim().is_empty() { line_count } else { end.trim().parse().ok()? },
+        ),
+        None => {
+            let line = spec.trim().parse().ok()?;
+            (line, line)
+        }
+    };
+    (start >= 1 && start <= end).then_some((start, end))
+}
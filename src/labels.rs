@@ -0,0 +1,110 @@
+use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+
+use crate::styles::typst_str;
+
+/// Names of the strings quoin adds to documents: outline titles, figure supplements, callout titles, the
+/// "Page X of Y" footer and the class and slide labels.
+pub const KEYS: [&str; 21] = [
+    "contents",
+    "list_of_figures",
+    "list_of_tables",
+    "list_of_listings",
+    "figure",
+    "table",
+    "listing",
+    "note",
+    "tip",
+    "important",
+    "warning",
+    "caution",
+    "page_of",
+    "notes",
+    "closing",
+    "memorandum",
+    "to",
+    "from",
+    "cc",
+    "date",
+    "subject",
+];
+
+/// Labels Typst translates itself for languages without a catalog.
+const TYPST_LOCALIZED: [&str; 4] = ["contents", "figure", "table", "listing"];
+
+/// Languages written right to left.
+const RTL_LANGS: [&str; 6] = ["ar", "he", "fa", "ur", "yi", "ps"];
+
+/// The primary language subtag: "fr" for "fr-CA", "zh" for "zh_Hans".
+pub(crate) fn primary(lang: &str) -> String {
+    lang.split(['-', '_']).next().unwrap_or_default().to_lowercase()
+}
+
+/// Built-in labels for a language ("fr-CA" uses "fr"). Languages without a catalog get the English labels,
+/// minus those Typst localizes itself, so the title of the outline and figure supplements stay translated.
+pub fn for_lang(lang: &str) -> BTreeMap<String, String> {
+    let catalog: BTreeMap<String, BTreeMap<String, String>> =
+        toml::from_str(include_str!("assets/labels.toml")).expect("the built-in label catalog is valid");
+    match catalog.get(&primary(lang)) {
+        Some(labels) => labels.clone(),
+        None => {
+            let mut labels = catalog["en"].clone();
+            labels.retain(|key, _| !TYPST_LOCALIZED.contains(&key.as_str()));
+            labels
+        }
+    }
+}
+
+/// Whether the language is written right to left (Arabic, Hebrew, Persian, ...).
+pub fn is_rtl(lang: &str) -> bool {
+    RTL_LANGS.contains(&primary(lang).as_str())
+}
+
+/// Whether the language is Chinese, Japanese or Korean.
+pub fn is_cjk(lang: &str) -> bool {
+    matches!(primary(lang).as_str(), "zh" | "ja" | "ko")
+}
+
+/// Checks label overrides (e.g. from a config file) for unknown names.
+pub fn validate(labels: &BTreeMap<String, String>) -> Result<()> {
+    match labels.keys().find(|key| !KEYS.contains(&key.as_str())) {
+        Some(key) => Err(anyhow!("Unknown label '{}' (expected one of {})", key, KEYS.join(", "))),
+        None => Ok(()),
+    }
+}
+
+/// Typst definitions for a language, with its overrides on top of the catalog: the `quoin-labels` dictionary
+/// (labels Typst localizes itself may be `auto`), `quoin-rtl`, `quoin-page-of()` and the figure supplements.
+/// `overrides` is keyed by language: those of the primary subtag ("fr") apply first, then those of the full
+/// tag ("fr-ca"). Right-to-left languages also get their text direction and a binding on the right, so
+/// inside/outside margins set in a header include are mirrored.
+pub fn prelude(lang: &str, overrides: &BTreeMap<String, BTreeMap<String, String>>) -> String {
+    let mut labels = for_lang(lang);
+    let mut tags = vec![primary(lang)];
+    if lang.to_lowercase() != tags[0] {
+        tags.push(lang.to_lowercase().replace('_', "-"));
+    }
+    for tag in &tags {
+        if let Some(overrides) = overrides.get(tag) {
+            labels.extend(overrides.iter().map(|(key, value)| (key.clone(), value.clone())));
+        }
+    }
+    let value = |key: &str| labels.get(key).map_or("auto".to_string(), |label| typst_str(label));
+    let entries: Vec<String> = KEYS.iter().map(|key| format!("{}: {}", key.replace('_', "-"), value(key))).collect();
+    let rtl = is_rtl(lang);
+
+    let mut prelude = format!("#let quoin-labels = (\n  {},\n)\n#let quoin-rtl = {}\n", entries.join(",\n  "), rtl);
+    prelude.push_str(
+        "#let quoin-page-of() = context quoin-labels.page-of.replace(\"{page}\", str(counter(page).get().first()))\
+         .replace(\"{total}\", str(counter(page).final().first()))\n",
+    );
+    for (kind, key) in [("image", "figure"), ("table", "table"), ("raw", "listing")] {
+        if labels.contains_key(key) {
+            prelude.push_str(&format!("#show figure.where(kind: {}): set figure(supplement: {})\n", kind, value(key)));
+        }
+    }
+    if rtl {
+        prelude.push_str("#set text(dir: rtl)\n#set page(binding: right)\n");
+    }
+    prelude
+}
@@ -1,3 +1,12 @@
 pub mod styles;
 pub mod pandoc;
 pub mod server;
+pub mod cache;
+pub mod frontmatter;
+pub mod images;
+pub mod remote;
+pub mod includes;
+pub mod variables;
+pub mod config;
+pub mod fonts;
+pub mod labels;
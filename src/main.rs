@@ -1,9 +1,13 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::{Parser, Subcommand};
+use quoin::config::Config;
+use quoin::fonts;
 use quoin::pandoc::PandocWrapper;
-use quoin::server::start_server;
-use quoin::styles::Profile;
+use quoin::remote::RemoteImageOptions;
+use quoin::server::{ServerOptions, start_server};
+use quoin::styles::{CitationMode, CodeTheme, ColumnLayout, DocumentClass, FontOptions, HeadingStyle, OutlineOptions, OutlinePosition, PrettyCode, Profile, SlideOptions, Theme, Typography};
 use std::path::Path;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "quoin")]
@@ -21,6 +25,7 @@ struct Cli {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // Parsed once at startup
 enum Commands {
     /// Converts a document to PDF
     #[command(next_display_order = None)] // Allows us to manualy control order
@@ -35,8 +40,17 @@ enum Commands {
         #[arg(short, long, display_order = 2)]
         output: Option<String>,
 
+        /// Output format: a document, or 16:9 slides split at H1/H2 headings and `---` rules
+        #[arg(long, value_parser = ["document", "slides"], default_value = "document", conflicts_with = "cheat_sheet", display_order = 3)]
+        format: String,
+
+        /// Document class, with its own title block, header/footer and heading rules (default: from the front
+        /// matter `class:` field, or the generic layout)
+        #[arg(long, value_parser = ["article", "report", "letter", "memo", "resume"], display_order = 4)]
+        class: Option<String>,
+
         // --- Layout Group ---
-        /// Use ultra-dense layout (8pt font, 2cm margins). Ideal for cheat sheets.
+        /// Use ultra-dense layout (8pt font, 2cm margins). See --cheat-sheet for a complete cheat-sheet layout.
         #[arg(long, group = "density_level", help_heading = "Layout Options", display_order = 10)]
         ultra_dense: bool,
 
@@ -52,10 +66,51 @@ enum Commands {
         #[arg(long, group = "density_level", help_heading = "Layout Options", display_order = 13)]
         comfort: bool,
 
-        /// Enable 2-column layout (Note: may cause overlapping with large tables)
-        #[arg(long, help_heading = "Layout Options", display_order = 14)]
+        /// Cheat-sheet layout: landscape, 3 columns (or --columns 4), H2 sections in color-coded boxes and a title bar
+        #[arg(long, conflicts_with_all = ["two_cols", "density_level"], help_heading = "Layout Options", display_order = 10)]
+        cheat_sheet: bool,
+
+        /// Reveal every list one item at a time (slides), not only those in `::: incremental` blocks
+        #[arg(long, help_heading = "Slides", display_order = 20)]
+        incremental: bool,
+
+        /// Print each slide's `::: notes` on a page after it (slides)
+        #[arg(long, help_heading = "Slides", display_order = 21)]
+        speaker_notes: bool,
+
+        /// Enable 2-column layout (same as --columns 2)
+        #[arg(long, conflicts_with = "columns", help_heading = "Layout Options", display_order = 14)]
         two_cols: bool,
 
+        /// Number of columns (1-6). Headings, tables and blocks marked {.span}, and tables too wide for a column,
+        /// run across all columns.
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=6), help_heading = "Layout Options", display_order = 14)]
+        columns: Option<u8>,
+
+        /// Space between columns (e.g. 1em, 5mm)
+        #[arg(long, value_name = "LENGTH", help_heading = "Layout Options", display_order = 17)]
+        column_gutter: Option<String>,
+
+        /// Draw a line between columns
+        #[arg(long, help_heading = "Layout Options", display_order = 18)]
+        column_rule: bool,
+
+        /// Balance column heights, so the last page does not end with one full and one empty column
+        #[arg(long, help_heading = "Layout Options", display_order = 19)]
+        balance_columns: bool,
+
+        /// Keep tables in their column even when they are too wide for it
+        #[arg(long, help_heading = "Layout Options", display_order = 19)]
+        no_span_wide: bool,
+
+        /// Paper size: a4, a5, letter, legal, another Typst paper name, or WIDTHxHEIGHT (e.g. 6inx9in)
+        #[arg(long, value_name = "SIZE", help_heading = "Layout Options", display_order = 15)]
+        paper: Option<String>,
+
+        /// Landscape orientation (::: portrait blocks stay upright)
+        #[arg(long, help_heading = "Layout Options", display_order = 16)]
+        landscape: bool,
+
         // --- Styling Group ---
         /// Enable "New Computer Modern" LaTeX-style font for that academic look
         #[arg(long, help_heading = "Formatting & Style", display_order = 20)]
@@ -65,30 +120,231 @@ enum Commands {
         #[arg(long, help_heading = "Formatting & Style", display_order = 21)]
         no_pretty_code: bool,
 
-        /// Disable alternative table styling (enabled by default)
+        /// Number the lines of every code block (blocks can opt in with {linenos=true})
         #[arg(long, help_heading = "Formatting & Style", display_order = 22)]
+        line_numbers: bool,
+
+        /// Code highlighting theme: light, dark, or a path to a .tmTheme file
+        #[arg(long, value_name = "THEME", default_value = "light", help_heading = "Formatting & Style", display_order = 23)]
+        code_theme: String,
+
+        /// Let long code lines overflow (clipped) instead of wrapping them
+        #[arg(long, help_heading = "Formatting & Style", display_order = 24)]
+        no_code_wrap: bool,
+
+        /// Disable alternative table styling (enabled by default)
+        #[arg(long, help_heading = "Formatting & Style", display_order = 25)]
         no_alt_table: bool,
 
         /// Restore default Pandoc table dimensions (overrides custom filter)
-        #[arg(long, help_heading = "Formatting & Style", display_order = 23)]
+        #[arg(long, help_heading = "Formatting & Style", display_order = 26)]
         table_dims: bool,
 
+        /// Disable callout boxes for GitHub-style alerts (> [!NOTE]) and ::: note divs
+        #[arg(long, help_heading = "Formatting & Style", display_order = 27)]
+        no_callouts: bool,
+
+        /// Disable rendering of mermaid, dot and plantuml code blocks to diagrams
+        #[arg(long, help_heading = "Formatting & Style", display_order = 28)]
+        no_diagrams: bool,
+
+        /// Disable cross-references ({#fig:x} labels and @fig:x references)
+        #[arg(long, help_heading = "Formatting & Style", display_order = 29)]
+        no_crossrefs: bool,
+
         // --- Document Features ---
         /// Enable section numbering (e.g., 1.1, 1.2)
         #[arg(long, help_heading = "Document Features", display_order = 30)]
         section_numbering: bool,
 
-        /// Append a Table of Contents (Outline) at the end of the document
+        /// Add a Table of Contents (Outline) to the document
         #[arg(long, help_heading = "Document Features", display_order = 31)]
         outline: bool,
 
+        /// Place the outline at the start (after the title block) or at the end of the document
+        #[arg(long, value_parser = ["start", "end"], default_value = "end", requires = "outline", help_heading = "Document Features", display_order = 32)]
+        outline_position: String,
+
+        /// Maximum heading level shown in the outline (e.g., 2)
+        #[arg(long, requires = "outline", help_heading = "Document Features", display_order = 33)]
+        outline_depth: Option<u8>,
+
+        /// Custom outline title (defaults to a title localized from the document language)
+        #[arg(long, requires = "outline", help_heading = "Document Features", display_order = 34)]
+        outline_title: Option<String>,
+
+        /// Indentation of nested outline entries as a Typst length (e.g., 1.5em)
+        #[arg(long, requires = "outline", help_heading = "Document Features", display_order = 35)]
+        outline_indent: Option<String>,
+
+        /// Add a list of figures after the outline
+        #[arg(long, requires = "outline", help_heading = "Document Features", display_order = 36)]
+        list_of_figures: bool,
+
+        /// Add a list of tables after the outline
+        #[arg(long, requires = "outline", help_heading = "Document Features", display_order = 37)]
+        list_of_tables: bool,
+
+        /// Add a list of code listings after the outline
+        #[arg(long, requires = "outline", help_heading = "Document Features", display_order = 38)]
+        list_of_listings: bool,
+
+        /// Bibliography file for citations (BibTeX, CSL JSON, ...). Can be repeated.
+        #[arg(long = "bibliography", value_name = "FILE", help_heading = "Document Features", display_order = 39)]
+        bibliographies: Vec<String>,
+
+        /// CSL style file used to format citations and the reference list
+        #[arg(long, value_name = "FILE", help_heading = "Document Features", display_order = 40)]
+        csl: Option<String>,
+
+        /// Resolve citations with Pandoc's citeproc or hand them to Typst's native bibliography
+        #[arg(long, value_parser = ["citeproc", "typst"], help_heading = "Document Features", display_order = 41)]
+        citations: Option<String>,
+
+        /// Disable ::: {when="..."} conditional blocks (they are then always kept)
+        #[arg(long, help_heading = "Document Features", display_order = 42)]
+        no_conditionals: bool,
+
+        /// Ruby (furigana) annotations: {漢字|かんじ}, {漢字|かん|じ} or [漢字]{.ruby rt="かんじ"}
+        #[arg(long, help_heading = "Document Features", display_order = 42)]
+        ruby: bool,
+
+        // --- Typography ---
+        /// Space between lines of a paragraph (e.g. 0.5em) [default: depends on density]
+        #[arg(long, value_name = "LENGTH", help_heading = "Typography", display_order = 43)]
+        leading: Option<String>,
+
+        /// Space between paragraphs (e.g. 0.8em) [default: depends on density]
+        #[arg(long, value_name = "LENGTH", help_heading = "Typography", display_order = 44)]
+        paragraph_spacing: Option<String>,
+
+        /// Indent the first line of paragraphs (e.g. 1.5em)
+        #[arg(long, value_name = "LENGTH", help_heading = "Typography", display_order = 45)]
+        first_line_indent: Option<String>,
+
+        /// Justified or ragged-right text
+        #[arg(long, value_name = "ALIGN", value_parser = ["justify", "ragged"], help_heading = "Typography", display_order = 46)]
+        text_align: Option<String>,
+
+        /// Hyphenate words using the patterns of the document language (-V lang=...) [default: auto]
+        #[arg(long, value_name = "MODE", value_parser = ["auto", "on", "off"], help_heading = "Typography", display_order = 47)]
+        hyphenate: Option<String>,
+
+        /// Allow widows and orphans (single lines at the top or bottom of a page) instead of avoiding them
+        #[arg(long, help_heading = "Typography", display_order = 48)]
+        no_widow_control: bool,
+
+        // --- Theme ---
+        /// Color theme: default, screen (light text on a dark page), or a path to a .toml theme file
+        #[arg(long, value_name = "THEME", help_heading = "Theme", display_order = 49)]
+        theme: Option<String>,
+
+        /// Image shown in the page header (e.g. a company logo)
+        #[arg(long, value_name = "FILE", help_heading = "Theme", display_order = 49)]
+        logo: Option<String>,
+
+        // --- Fonts ---
+        /// Use a font pairing for body, headings, code and math
+        #[arg(long, value_name = "PAIRING", value_parser = ["academic", "modern-sans", "technical"], help_heading = "Fonts", display_order = 50)]
+        fonts: Option<String>,
+
+        /// Body font, with comma-separated fallbacks (e.g. "Inter,Noto Sans")
+        #[arg(long, value_name = "FAMILIES", value_delimiter = ',', help_heading = "Fonts", display_order = 51)]
+        body_font: Vec<String>,
+
+        /// Heading font, with comma-separated fallbacks
+        #[arg(long, value_name = "FAMILIES", value_delimiter = ',', help_heading = "Fonts", display_order = 52)]
+        heading_font: Vec<String>,
+
+        /// Monospace font for code, with comma-separated fallbacks
+        #[arg(long, value_name = "FAMILIES", value_delimiter = ',', help_heading = "Fonts", display_order = 53)]
+        mono_font: Vec<String>,
+
+        /// Math font (needs an OpenType MATH table), with comma-separated fallbacks
+        #[arg(long, value_name = "FAMILIES", value_delimiter = ',', help_heading = "Fonts", display_order = 54)]
+        math_font: Vec<String>,
+
+        /// Directory with extra fonts for Typst. Can be repeated.
+        #[arg(long = "font-path", value_name = "DIR", help_heading = "Fonts", display_order = 55)]
+        font_paths: Vec<String>,
+
+        /// Ignore system fonts, using only --font-path directories and the fonts built into Typst (hermetic builds)
+        #[arg(long, help_heading = "Fonts", display_order = 56)]
+        ignore_system_fonts: bool,
+
+        // --- Headings ---
+        /// Heading numbering pattern, e.g. 1.1, 1.a, I.1 or A.1 (implies numbered headings)
+        #[arg(long, value_name = "PATTERN", help_heading = "Headings", display_order = 57)]
+        heading_numbering: Option<String>,
+
+        /// Numbering pattern from the first `# Title {.appendix}` heading on, e.g. A.1
+        #[arg(long, value_name = "PATTERN", help_heading = "Headings", display_order = 58)]
+        appendix_numbering: Option<String>,
+
+        /// Start every level-1 heading on a new page
+        #[arg(long, help_heading = "Headings", display_order = 59)]
+        chapter_page_breaks: bool,
+
         // --- Advanced ---
         /// Output Typst source instead of PDF (or in addition to it if output ends in .typ)
-        #[arg(long, help_heading = "Advanced", display_order = 40)]
+        #[arg(long, help_heading = "Advanced", display_order = 60)]
         typ: bool,
 
-        /// Override custom variables or set Typst metadata (e.g., -V lang=fr -V cols=2)
-        #[arg(short = 'V', long = "variable", help_heading = "Advanced", display_order = 41)]
+        /// Extra directory to search for images and other resources (after the input's directory). Can be repeated.
+        #[arg(long = "resource-path", value_name = "DIR", help_heading = "Advanced", display_order = 62)]
+        resource_paths: Vec<String>,
+
+        /// Warn about local images that cannot be found instead of failing
+        #[arg(long, help_heading = "Advanced", display_order = 62)]
+        allow_missing_images: bool,
+
+        /// Never download remote images; only previously cached copies are used
+        #[arg(long, help_heading = "Advanced", display_order = 63)]
+        offline: bool,
+
+        /// Replace remote images that cannot be fetched with a placeholder instead of failing
+        #[arg(long, help_heading = "Advanced", display_order = 64)]
+        image_placeholder: bool,
+
+        /// Timeout for each remote image download, in seconds
+        #[arg(long, value_name = "SECONDS", default_value_t = 30, help_heading = "Advanced", display_order = 65)]
+        fetch_timeout: u64,
+
+        /// Maximum size of a remote image, in megabytes
+        #[arg(long, value_name = "MB", default_value_t = 20, help_heading = "Advanced", display_order = 66)]
+        max_image_size: u64,
+
+        /// Do not expand `!include file.md` lines and `include="file"` code blocks
+        #[arg(long, help_heading = "Advanced", display_order = 67)]
+        no_includes: bool,
+
+        /// Fail when the body uses an undefined `{{variable}}` instead of leaving it as-is
+        #[arg(long, help_heading = "Advanced", display_order = 68)]
+        strict_variables: bool,
+
+        // --- Customization ---
+        /// Config file with includes, filters, variables and presets [default: quoin.toml next to the input if present]
+        #[arg(long, value_name = "FILE", help_heading = "Customization", display_order = 70)]
+        config: Option<String>,
+
+        /// Apply a [presets.<NAME>] table from the config file on top of its top-level settings
+        #[arg(long, value_name = "NAME", help_heading = "Customization", display_order = 71)]
+        preset: Option<String>,
+
+        /// Append a Typst file to the document header, after quoin's styles. Can be repeated.
+        #[arg(long = "include-header", value_name = "FILE", help_heading = "Customization", display_order = 72)]
+        include_headers: Vec<String>,
+
+        /// Append a Typst file after the document body. Can be repeated.
+        #[arg(long = "include-after", value_name = "FILE", help_heading = "Customization", display_order = 73)]
+        include_afters: Vec<String>,
+
+        /// Run a Lua filter after quoin's own filters. Can be repeated.
+        #[arg(long = "lua-filter", value_name = "FILE", help_heading = "Customization", display_order = 74)]
+        lua_filters: Vec<String>,
+
+        /// Override custom variables or set Typst metadata (e.g., -V lang=fr -V cols=2); also available as {{key}} in the body
+        #[arg(short = 'V', long = "variable", help_heading = "Advanced", display_order = 61)]
         variables: Vec<String>,
     },
 
@@ -105,6 +361,33 @@ enum Commands {
         /// Allow connections from external interfaces (binds to 0.0.0.0)
         #[arg(long)]
         allow_external: bool,
+
+        /// Download remote images in submitted documents. Off by default, since clients could make the server fetch any URL
+        #[arg(long)]
+        remote_images: bool,
+
+        /// Render diagram blocks in submitted documents. Off by default, since diagram tools can read local files
+        #[arg(long)]
+        diagrams: bool,
+    },
+
+    /// Lists the font families available to Typst, with variants and script coverage
+    Fonts {
+        /// Extra font directory to include. Can be repeated.
+        #[arg(long = "font-path", value_name = "DIR")]
+        font_paths: Vec<String>,
+
+        /// Only list fonts from font paths and those built into Typst
+        #[arg(long)]
+        ignore_system_fonts: bool,
+
+        /// Config file whose font_paths are included [default: ./quoin.toml if present]
+        #[arg(long, value_name = "FILE")]
+        config: Option<String>,
+
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -122,7 +405,9 @@ async fn main() -> Result<()> {
         _ => "debug,quoin=trace",
     };
 
+    // Logs go to stderr so that `-o -` and `fonts --json` output stays clean
     tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
         .with_env_filter(filter)
         .with_target(false)
         .init();
@@ -133,18 +418,79 @@ async fn main() -> Result<()> {
         Commands::Convert {
             input,
             output,
+            format,
+            class,
             ultra_dense,
             dense,
             standard: _standard,
             comfort,
             two_cols,
+            cheat_sheet,
+            incremental,
+            speaker_notes,
+            columns,
+            column_gutter,
+            column_rule,
+            balance_columns,
+            no_span_wide,
+            paper,
+            landscape,
             latex_font,
             no_alt_table,
             table_dims,
             no_pretty_code,
+            line_numbers,
+            code_theme,
+            no_code_wrap,
+            no_callouts,
+            no_diagrams,
+            no_crossrefs,
             section_numbering,
             typ,
             outline,
+            outline_position,
+            outline_depth,
+            outline_title,
+            outline_indent,
+            list_of_figures,
+            list_of_tables,
+            list_of_listings,
+            bibliographies,
+            csl,
+            citations,
+            no_conditionals,
+            ruby,
+            leading,
+            paragraph_spacing,
+            first_line_indent,
+            text_align,
+            hyphenate,
+            no_widow_control,
+            theme,
+            logo,
+            fonts,
+            body_font,
+            heading_font,
+            mono_font,
+            math_font,
+            font_paths,
+            ignore_system_fonts,
+            heading_numbering,
+            appendix_numbering,
+            chapter_page_breaks,
+            resource_paths,
+            allow_missing_images,
+            offline,
+            image_placeholder,
+            fetch_timeout,
+            max_image_size,
+            no_includes,
+            strict_variables,
+            config,
+            preset,
+            include_headers,
+            include_afters,
+            lua_filters,
             variables,
         } => {
             let mut profile = Profile::new();
@@ -152,6 +498,11 @@ async fn main() -> Result<()> {
             // Set global defaults (grid, breakable blocks, etc.)
             profile.set_global_defaults();
 
+            // Conditional blocks are resolved before any other filter sees them
+            if !*no_conditionals {
+                profile.set_conditionals();
+            }
+
             // Determine density (default to standard)
             let density = if *ultra_dense {
                 "ultra-dense"
@@ -166,12 +517,16 @@ async fn main() -> Result<()> {
             // Set density settings
             profile.set_density(density);
 
-            // Set column count
-            profile.set_two_cols(*two_cols);
+            // `::: landscape` blocks get pages of their own
+            profile.set_orientation_blocks();
 
-            // Warning 2-column layouts
-            if *two_cols {
-                eprintln!("Warning: Using 2 columns may cause text collisions.");
+            // Cheat sheets override density, orientation and columns
+            if *cheat_sheet {
+                profile.set_cheat_sheet(columns.unwrap_or(3))?;
+            }
+            profile.set_format(format)?;
+            if let Some(class) = class {
+                profile.set_class(DocumentClass::parse(class)?);
             }
 
             // Apply LaTeX-style font if requested
@@ -191,7 +546,26 @@ async fn main() -> Result<()> {
 
             // Apply pretty-code modifier unless disabled
             if !*no_pretty_code {
-                profile.set_pretty_code();
+                profile.set_pretty_code_with(&PrettyCode {
+                    line_numbers: *line_numbers,
+                    theme: CodeTheme::parse(code_theme),
+                    wrap: !*no_code_wrap,
+                })?;
+            }
+
+            // Apply callout boxes unless disabled
+            if !*no_callouts {
+                profile.set_callouts();
+            }
+
+            // Render diagram code blocks unless disabled
+            if !*no_diagrams {
+                profile.set_diagrams();
+            }
+
+            // Resolve cross-references unless disabled
+            if !*no_crossrefs {
+                profile.set_crossrefs();
             }
 
             // Apply section-numbering if requested
@@ -201,7 +575,145 @@ async fn main() -> Result<()> {
 
             // Apply outline if requested
             if *outline {
-                profile.set_outline();
+                profile.set_outline_with(&OutlineOptions {
+                    position: OutlinePosition::parse(outline_position),
+                    depth: *outline_depth,
+                    title: outline_title.clone(),
+                    indent: outline_indent.clone(),
+                    list_of_figures: *list_of_figures,
+                    list_of_tables: *list_of_tables,
+                    list_of_listings: *list_of_listings,
+                })?;
+            }
+
+            // Apply citation settings; a bibliography alone enables citeproc
+            if let Some(mode) = citations {
+                profile.set_citations(CitationMode::parse(mode));
+            }
+            for bibliography in bibliographies {
+                profile.add_bibliography(bibliography);
+            }
+            if let Some(csl) = csl {
+                profile.set_csl(csl);
+            }
+
+            if *ruby {
+                profile.set_ruby();
+            }
+
+            for dir in resource_paths {
+                profile.add_resource_path(dir);
+            }
+            profile.set_allow_missing_images(*allow_missing_images);
+
+            // Download remote images into the local cache
+            profile.set_remote_images(RemoteImageOptions {
+                offline: *offline,
+                placeholder: *image_placeholder,
+                timeout: Duration::from_secs(*fetch_timeout),
+                max_bytes: max_image_size.saturating_mul(1024 * 1024),
+                ..RemoteImageOptions::default()
+            });
+
+            profile.set_includes(!*no_includes);
+            profile.set_variable_substitution(*strict_variables);
+
+            // User customizations come after the built-in modifiers: config file, then preset, then command line
+            // quoin.toml is looked up next to the input, or in the working directory for stdin
+            let config_dir = match Path::new(input).parent() {
+                Some(dir) if input != "-" && !dir.as_os_str().is_empty() => dir.to_path_buf(),
+                _ => std::env::current_dir()?,
+            };
+            let config = Config::discover(config.as_deref().map(Path::new), &config_dir)?;
+            match (&config, preset) {
+                (Some(config), preset) => config.apply(&mut profile, preset.as_deref())?,
+                (None, Some(preset)) => return Err(anyhow!("--preset {} requires a config file", preset)),
+                (None, None) => {}
+            }
+            // Columns, paper size and orientation given on the command line; wide tables span all columns
+            profile.set_columns(&ColumnLayout {
+                count: columns.or(two_cols.then_some(2)),
+                gutter: column_gutter.clone(),
+                rule: column_rule.then_some(true),
+                balance: balance_columns.then_some(true),
+                span_wide: no_span_wide.then_some(false),
+            })?;
+            if let Some(paper) = paper {
+                profile.set_paper(paper)?;
+            }
+            if *landscape {
+                profile.set_landscape(true);
+            }
+            // Fonts compiled into the binary (embedded-fonts feature), then command line font directories
+            if let Some(dir) = fonts::embedded_font_dir()? {
+                profile.add_font_path(dir)?;
+            }
+            for dir in font_paths {
+                profile.add_font_path(dir)?;
+            }
+            if *ignore_system_fonts {
+                profile.set_ignore_system_fonts(true);
+            }
+            if let Some(spec) = theme {
+                profile.set_theme(&Theme::resolve(spec)?)?;
+            }
+            if let Some(logo) = logo {
+                profile.set_theme(&Theme { logo: Some(logo.into()), ..Theme::default() })?;
+            }
+            // Slide options also apply to decks selected in the config file
+            let slides = SlideOptions {
+                incremental: incremental.then_some(true),
+                speaker_notes: speaker_notes.then_some(true),
+            };
+            if slides != SlideOptions::default() {
+                if profile.slides.is_some() {
+                    profile.set_slides(&slides)?;
+                } else {
+                    tracing::warn!("--incremental and --speaker-notes only apply to --format slides");
+                }
+            }
+            let typography = Typography {
+                leading: leading.clone(),
+                paragraph_spacing: paragraph_spacing.clone(),
+                first_line_indent: first_line_indent.clone(),
+                justify: text_align.as_deref().map(|align| align == "justify"),
+                hyphenate: match hyphenate.as_deref() {
+                    Some("on") => Some(true),
+                    Some("off") => Some(false),
+                    _ => None,
+                },
+                widow_orphan_control: no_widow_control.then_some(false),
+            };
+            if typography != Typography::default() {
+                profile.set_typography(&typography)?;
+            }
+            let headings = HeadingStyle {
+                numbering: heading_numbering.clone(),
+                appendix: appendix_numbering.clone(),
+                page_break: chapter_page_breaks.then_some(true),
+                ..HeadingStyle::default()
+            };
+            if headings != HeadingStyle::default() {
+                profile.set_heading_style(&headings)?;
+            }
+            let font_options = FontOptions {
+                pairing: fonts.clone(),
+                body: body_font.clone(),
+                heading: heading_font.clone(),
+                mono: mono_font.clone(),
+                math: math_font.clone(),
+            };
+            if font_options != FontOptions::default() {
+                profile.set_fonts(&font_options)?;
+            }
+            for path in include_headers {
+                profile.add_header_include_file(Path::new(path))?;
+            }
+            for path in include_afters {
+                profile.add_after_body_include_file(Path::new(path))?;
+            }
+            for path in lua_filters {
+                profile.add_lua_filter_file(Path::new(path))?;
             }
 
             // Apply custom variable overrides
@@ -267,8 +779,43 @@ async fn main() -> Result<()> {
             }
             tracing::info!("Conversion completed successfully.");
         }
-        Commands::Server { port, api_only, allow_external } => {
-            start_server(*port, *api_only, *allow_external).await?;
+        Commands::Server { port, api_only, allow_external, remote_images, diagrams } => {
+            let options = ServerOptions { remote_images: *remote_images, diagrams: *diagrams };
+            start_server(*port, *api_only, *allow_external, options).await?;
+        }
+        Commands::Fonts { font_paths, ignore_system_fonts, config, json } => {
+            let mut profile = Profile::new();
+            if let Some(config) = Config::discover(config.as_deref().map(Path::new), &std::env::current_dir()?)? {
+                for dir in &config.defaults.font_paths {
+                    profile.add_font_path(dir)?;
+                }
+                if let Some(ignore) = config.defaults.ignore_system_fonts {
+                    profile.set_ignore_system_fonts(ignore);
+                }
+            }
+            if let Some(dir) = fonts::embedded_font_dir()? {
+                profile.add_font_path(dir)?;
+            }
+            for dir in font_paths {
+                profile.add_font_path(dir)?;
+            }
+            if *ignore_system_fonts {
+                profile.set_ignore_system_fonts(true);
+            }
+
+            let families = fonts::list_fonts(&profile.font_paths, profile.ignore_system_fonts);
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&families)?);
+            } else {
+                for family in &families {
+                    println!("{} ({})", family.name, family.source);
+                    if !family.scripts.is_empty() {
+                        println!("    scripts:  {}", family.scripts.join(", "));
+                    }
+                    let variants: Vec<String> = family.variants.iter().map(|v| v.to_string()).collect();
+                    println!("    variants: {}", variants.join(", "));
+                }
+            }
         }
     }
 
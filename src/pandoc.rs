@@ -1,9 +1,16 @@
-use std::process::{Command, Stdio};
+use std::process::Command;
 use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 use anyhow::{Result, anyhow};
 use which::which;
-use crate::styles::Profile;
-use tracing::{info, debug, error};
+use crate::fonts;
+use crate::frontmatter;
+use crate::images;
+use crate::includes;
+use crate::remote;
+use crate::variables;
+use crate::styles::{CitationMode, DocumentClass, Profile, typst_path};
+use tracing::{info, debug, error, warn};
 
 pub struct PandocWrapper;
 
@@ -16,18 +23,64 @@ impl PandocWrapper {
             return Err(anyhow!("Pandoc not found in system PATH. Please install Pandoc."));
         }
 
-        let mut cmd = Command::new("pandoc");
+        // Read the whole input up front so it can be inspected and preprocessed before Pandoc runs
+        let source = if input == "-" {
+            let mut buffer = String::new();
+            io::stdin().read_to_string(&mut buffer)?;
+            buffer
+        } else {
+            std::fs::read_to_string(input).map_err(|e| anyhow!("Failed to read input file {}: {}", input, e))?
+        };
 
-        // Set input format to GFM
-        cmd.arg("-f").arg("gfm");
+        // Honor a front matter `bibliography:` even when citations were not requested explicitly
+        let mut profile = profile.clone();
+        let front_matter = frontmatter::parse(&source).unwrap_or_default();
+        if profile.citations.is_none() && front_matter.contains_key("bibliography") {
+            debug!("Front matter declares a bibliography, enabling citations");
+            profile.set_citations(CitationMode::default());
+        }
 
-        // Set input
-        if input == "-" {
-            cmd.stdin(Stdio::piped());
-        } else {
-            cmd.arg(input);
+        // The front matter language wins over the metadata file, for generated labels as for Pandoc
+        if let Some(lang) = front_matter.get("lang").and_then(|value| value.as_str()) {
+            profile.metadata.lang = lang.to_string();
         }
 
+        // A front matter `class:` applies unless a class was chosen on the command line or in the config.
+        // The class's fields come from the front matter and `-V` variables.
+        if profile.class.is_none()
+            && let Some(name) = front_matter.get("class").and_then(|value| value.as_str())
+        {
+            profile.set_class(DocumentClass::parse(name)?);
+        }
+        if let Some(class) = profile.class {
+            let mut fields = front_matter.clone();
+            fields.extend(profile.metadata.extra.clone());
+            let prelude = class.fields_prelude(&fields)?;
+            profile.header_includes.push(prelude);
+        }
+
+        // Warn about configured fonts Typst would silently replace, and about CJK text without a CJK font
+        let cjk_rules = profile.cjk_rules();
+        if cjk_rules.is_some() || profile.fonts.roles().iter().any(|(_, families)| !families.is_empty()) {
+            let available = fonts::cached_fonts(&profile.font_paths, profile.ignore_system_fonts);
+            for warning in fonts::missing_font_warnings(&profile.fonts, &available) {
+                warn!("{}", warning);
+            }
+            if let Some(warning) = fonts::cjk_font_warning(&profile.metadata.lang, &available) {
+                warn!("{}", warning);
+            }
+        }
+        // After the font and typography rules, which the CJK rules build on
+        if let Some(rules) = cjk_rules {
+            profile.before_body_includes.push(rules);
+        }
+        let profile = &profile;
+
+        let mut cmd = Command::new("pandoc");
+
+        // Set input format (GFM by default), plus any extensions required by enabled modifiers
+        cmd.arg("-f").arg(reader_format(profile));
+
         // Set output
         let actual_output = if output == "-" {
             if is_typst {
@@ -41,19 +94,203 @@ impl PandocWrapper {
         debug!("Pandoc output path set to: {}", actual_output);
         cmd.arg("-o").arg(&actual_output);
 
-        // Set engine or output format
+        // Relative paths are looked up next to the input first, then in the working directory
+        // and any extra resource paths. A sandboxed conversion only looks in its sandbox.
+        let cwd = std::env::current_dir()?;
+        let sandbox = profile.image_sandbox.as_deref().map(absolute_path).transpose()?;
+        let search_dirs = match &sandbox {
+            Some(sandbox) => vec![sandbox.clone()],
+            None => {
+                let input_dir = if input == "-" {
+                    cwd.clone()
+                } else {
+                    absolute_path(Path::new(input))?.parent().map(Path::to_path_buf).unwrap_or_else(|| cwd.clone())
+                };
+                let mut search_dirs = vec![input_dir];
+                for dir in std::iter::once(&cwd).chain(&profile.resource_paths) {
+                    let dir = absolute_path(dir)?;
+                    if !search_dirs.contains(&dir) {
+                        search_dirs.push(dir);
+                    }
+                }
+                search_dirs
+            }
+        };
+        debug!("Resource path: {:?}", search_dirs);
+        cmd.arg("--resource-path").arg(std::env::join_paths(&search_dirs)?);
+
+        // Inline included files first, so their images and code go through the same stages
+        let source = if profile.expand_includes {
+            let input_dir = search_dirs.first().cloned().unwrap_or_else(|| cwd.clone());
+            let input_file = (input != "-").then(|| Path::new(input));
+            let expanded = includes::expand_includes(&source, &input_dir, input_file)?;
+            for file in &expanded.files {
+                debug!("Included {}", file.display());
+            }
+            expanded.markdown
+        } else {
+            source
+        };
+
+        // `{{name}}` placeholders, including those in included files, are filled in by the variables filter
+        let source = if profile.substitute_variables { variables::mark_escapes(&source) } else { source };
+
+        // Download remote images into the cache; they are then resolved like local ones
+        let source = match &profile.remote_images {
+            Some(options) => remote::localize_remote_images(&source, options)?,
+            None => source,
+        };
+
+        // Typst resolves relative paths against the .typ file, and absolute ones against its project root
+        // rather than the filesystem root, so every path handed to it is made relative to the .typ file.
+        // For PDF output Pandoc writes its intermediate .typ file to the working directory.
+        let typ_dir = if is_typst && output != "-" {
+            absolute_path(Path::new(&actual_output))?.parent().map(Path::to_path_buf).unwrap_or_else(|| cwd.clone())
+        } else {
+            cwd.clone()
+        };
+        cmd.env("QUOIN_TYPST_DIR", &typ_dir);
+        if profile.allow_missing_images {
+            cmd.env("QUOIN_ALLOW_MISSING_IMAGES", "1");
+        }
+        if let Some(sandbox) = &sandbox {
+            cmd.env("QUOIN_IMAGE_SANDBOX", sandbox);
+            if let Some(options) = &profile.remote_images {
+                cmd.env("QUOIN_REMOTE_IMAGE_CACHE", absolute_path(&options.cache_dir)?);
+            }
+        }
+
+        // Set input
+        let input_path = format!("{}_input.md", actual_output);
+        debug!("Writing preprocessed input to {}", input_path);
+        std::fs::write(&input_path, &source)?;
+        cmd.arg(&input_path);
+
+        // Set engine or output format. Citeproc output needs Typst's native citations disabled.
+        let citeproc = profile.citations == Some(CitationMode::Citeproc);
+        let writer = if citeproc { "typst-citations" } else { "typst" };
         if is_typst {
-            cmd.arg("-t").arg("typst");
+            cmd.arg("-t").arg(writer);
             cmd.arg("--standalone");
         } else {
+            if citeproc {
+                cmd.arg("-t").arg(writer);
+            }
             cmd.arg("--pdf-engine=typst");
         }
 
+        // Citation mode for the bibliography filter
+        if let Some(mode) = profile.citations {
+            cmd.env("QUOIN_CITATIONS", if mode == CitationMode::Typst { "typst" } else { "citeproc" });
+        }
+
+        // Variables for placeholders and conditional blocks; they take precedence over metadata
+        if !profile.variables.is_empty() {
+            let variables: Vec<String> = profile.variables.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            cmd.env("QUOIN_VARIABLES", variables.join("\n"));
+        }
+        if profile.strict_variables {
+            cmd.env("QUOIN_STRICT_VARIABLES", "1");
+        }
+
+        // Column count for the column sections filter
+        if profile.columns.count() > 1 {
+            cmd.env("QUOIN_COLUMNS", profile.columns.count().to_string());
+            if profile.columns.span_wide == Some(false) {
+                cmd.env("QUOIN_SPAN_WIDE_TABLES", "0");
+            }
+            if profile.level_one_page_breaks() {
+                cmd.env("QUOIN_H1_PAGE_BREAKS", "1");
+            }
+        }
+
+        // Lists revealed one item at a time by the slides filter
+        if let Some(slides) = &profile.slides
+            && slides.incremental == Some(true)
+        {
+            cmd.env("QUOIN_INCREMENTAL", "1");
+        }
+
+        // Numbering pattern for the appendix filter
+        if let Some(pattern) = &profile.headings.appendix {
+            cmd.env("QUOIN_APPENDIX_NUMBERING", pattern);
+        }
+
+        // Point the diagram filter at its cache
+        if let Some(cache_dir) = &profile.diagram_cache_dir {
+            std::fs::create_dir_all(cache_dir)?;
+            cmd.env("QUOIN_DIAGRAM_CACHE", cache_dir);
+        }
+
+        // Write the files the includes reference (themes, ...)
+        for file in &profile.support_files {
+            if let Some(content) = &file.content {
+                if let Some(dir) = file.path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                if std::fs::read_to_string(&file.path).ok().as_ref() != Some(content) {
+                    debug!("Writing support file {}", file.path.display());
+                    std::fs::write(&file.path, content)?;
+                }
+            }
+        }
+
+        // Typst only reads files below its project root: use the deepest directory covering
+        // the working directory, the resource paths, caches and support files. The images filter
+        // warns about images outside of it.
+        if !is_typst {
+            let mut root_dirs = vec![cwd.clone()];
+            root_dirs.extend(search_dirs.iter().cloned());
+            root_dirs.extend(profile.remote_images.iter().map(|options| options.cache_dir.clone()));
+            root_dirs.extend(profile.diagram_cache_dir.iter().cloned());
+            root_dirs.extend(profile.support_files.iter().filter_map(|f| f.path.parent().map(Path::to_path_buf)));
+            let root = common_ancestor(&root_dirs);
+            debug!("Using Typst root {}", root.display());
+            cmd.arg(format!("--pdf-engine-opt=--root={}", root.display()));
+            cmd.env("QUOIN_TYPST_ROOT", &root);
+        }
+
+        // Font directories for the Typst engine
+        if !is_typst {
+            for path in &profile.font_paths {
+                cmd.arg(format!("--pdf-engine-opt=--font-path={}", path.display()));
+            }
+            if profile.ignore_system_fonts {
+                cmd.arg("--pdf-engine-opt=--ignore-system-fonts");
+            }
+        } else if !profile.font_paths.is_empty() || profile.ignore_system_fonts {
+            let mut flags: Vec<String> =
+                profile.font_paths.iter().map(|p| format!("--font-path {}", p.display())).collect();
+            if profile.ignore_system_fonts {
+                flags.push("--ignore-system-fonts".to_string());
+            }
+            info!("Compile the .typ output with `typst compile {}` to use the configured fonts", flags.join(" "));
+        }
+
         // Create temporary metadata YAML file
         let metadata_path = format!("{}_metadata.yaml", actual_output);
         let header_path = format!("{}_header.typ", actual_output);
+        let before_body_path = format!("{}_before_body.typ", actual_output);
         let after_body_path = format!("{}_after_body.typ", actual_output);
         let lua_path = format!("{}_table.lua", actual_output);
+        let images_lua_path = format!("{}_images.lua", actual_output);
+        let variables_lua_path = format!("{}_variables.lua", actual_output);
+        // `require("quoin_typst")` finds the shared helpers at `<output>_quoin_typst.lua`
+        let helpers_lua_path = format!("{}_quoin_typst.lua", actual_output);
+        std::fs::write(&helpers_lua_path, include_str!("assets/lua/quoin_typst.lua"))?;
+        // Lua prefers the versioned variables; ";;" stands for the default path
+        let lua_path_vars = ["LUA_PATH_5_4", "LUA_PATH_5_3", "LUA_PATH"];
+        let user_lua_path = lua_path_vars.iter().find_map(|var| std::env::var(var).ok()).unwrap_or(";".to_string());
+        for var in lua_path_vars {
+            cmd.env(var, format!("{}_?.lua;{}", actual_output, user_lua_path));
+        }
+
+        // Fill in placeholders first, so every other filter sees the final text
+        if profile.substitute_variables {
+            debug!("Writing Lua variables filter to {}", variables_lua_path);
+            std::fs::write(&variables_lua_path, include_str!("assets/lua/variables.lua"))?;
+            cmd.arg("--lua-filter").arg(&variables_lua_path);
+        }
         
         // Write Lua filter
         if profile.use_lua_table_filter {
@@ -63,25 +300,61 @@ impl PandocWrapper {
             cmd.arg("--lua-filter").arg(&lua_path);
         }
 
+        // Resolve local images before the other filters add their own (diagrams, ...)
+        debug!("Writing Lua images filter to {}", images_lua_path);
+        std::fs::write(&images_lua_path, include_str!("assets/lua/images.lua"))?;
+        cmd.arg("--lua-filter").arg(&images_lua_path);
+
+        // Write additional Lua filters
+        let filter_paths: Vec<String> = (0..profile.lua_filters.len())
+            .map(|i| format!("{}_filter_{}.lua", actual_output, i))
+            .collect();
+        for (filter, filter_path) in profile.lua_filters.iter().zip(&filter_paths) {
+            debug!("Writing Lua filter to {}", filter_path);
+            std::fs::write(filter_path, filter)?;
+            cmd.arg("--lua-filter").arg(filter_path);
+        }
+
+        // User filters run after quoin's own
+        for filter_path in &profile.lua_filter_files {
+            cmd.arg("--lua-filter").arg(filter_path);
+        }
+
+        // Citeproc must run after the filters that place the reference list
+        if citeproc {
+            cmd.arg("--citeproc");
+        }
+
         // Serialize metadata to YAML
         debug!("Writing metadata to {}", metadata_path);
         let yaml_content = serde_yaml::to_string(&profile.metadata)?;
         std::fs::write(&metadata_path, yaml_content)?;
         cmd.arg("--metadata-file").arg(&metadata_path);
 
-        // Write header includes to a separate file to prevent escaping
-        if !profile.header_includes.is_empty() {
-            debug!("Writing header includes to {}", header_path);
-            let header_content = profile.header_includes.join("\n");
-            std::fs::write(&header_path, header_content)?;
-            cmd.arg("--include-in-header").arg(&header_path);
+        // Write header includes to a separate file to prevent escaping. The theme comes first, since the
+        // other includes read their colors from it, and the page setup last.
+        debug!("Writing header includes to {}", header_path);
+        let mut header_content = profile.labels_prelude();
+        header_content.push_str(&profile.theme.prelude()?);
+        header_content.push_str(&profile.header_includes.join("\n"));
+        header_content.push('\n');
+        header_content.push_str(&profile.page_setup());
+        std::fs::write(&header_path, rebase_support_files(&header_content, profile, &typ_dir))?;
+        cmd.arg("--include-in-header").arg(&header_path);
+
+        // Write before body includes to a separate file
+        if !profile.before_body_includes.is_empty() {
+            debug!("Writing before-body includes to {}", before_body_path);
+            let before_body_content = profile.before_body_includes.join("\n");
+            std::fs::write(&before_body_path, rebase_support_files(&before_body_content, profile, &typ_dir))?;
+            cmd.arg("--include-before-body").arg(&before_body_path);
         }
 
         // Write after body includes to a separate file
         if !profile.after_body_includes.is_empty() {
             debug!("Writing after-body includes to {}", after_body_path);
             let after_body_content = profile.after_body_includes.join("\n");
-            std::fs::write(&after_body_path, after_body_content)?;
+            std::fs::write(&after_body_path, rebase_support_files(&after_body_content, profile, &typ_dir))?;
             cmd.arg("--include-after-body").arg(&after_body_path);
         }
 
@@ -91,14 +364,6 @@ impl PandocWrapper {
             e
         })?;
 
-        // If stdin is used, pipe current stdin to child
-        if input == "-" {
-            let mut stdin = child.stdin.take().ok_or_else(|| anyhow!("Failed to open stdin"))?;
-            let mut buffer = Vec::new();
-            io::stdin().read_to_end(&mut buffer)?;
-            stdin.write_all(&buffer)?;
-        }
-
         let status = child.wait().map_err(|e| {
             error!("Pandoc process wait failed: {}", e);
             e
@@ -106,12 +371,20 @@ impl PandocWrapper {
         
         // Cleanup temporary files
         debug!("Cleaning up temporary files...");
+        let _ = std::fs::remove_file(&input_path);
         let _ = std::fs::remove_file(&metadata_path);
         let _ = std::fs::remove_file(&header_path);
+        let _ = std::fs::remove_file(&before_body_path);
         let _ = std::fs::remove_file(&after_body_path);
+        let _ = std::fs::remove_file(&images_lua_path);
+        let _ = std::fs::remove_file(&variables_lua_path);
+        let _ = std::fs::remove_file(&helpers_lua_path);
         if profile.use_lua_table_filter {
             let _ = std::fs::remove_file(&lua_path);
         }
+        for filter_path in &filter_paths {
+            let _ = std::fs::remove_file(filter_path);
+        }
 
         if !status.success() {
             return Err(anyhow!("Pandoc execution failed with status: {}", status));
@@ -129,3 +402,53 @@ impl PandocWrapper {
         Ok(())
     }
 }
+
+/// Replaces the absolute support file paths quoted in `includes` with paths relative to `typ_dir`.
+fn rebase_support_files(includes: &str, profile: &Profile, typ_dir: &Path) -> String {
+    let mut includes = includes.to_string();
+    for file in &profile.support_files {
+        let relative = images::relative_path(&file.path, typ_dir);
+        includes = includes.replace(&typst_path(&file.path), &typst_path(&relative));
+    }
+    includes
+}
+
+/// Extensions only understood by the CommonMark-based readers (GFM, CommonMark).
+const COMMONMARK_ONLY_EXTENSIONS: &[&str] = &["attributes", "alerts"];
+
+/// Builds the Pandoc `-f` argument from the profile's input format and reader extensions.
+fn reader_format(profile: &Profile) -> String {
+    let commonmark = matches!(profile.input_format.as_str(), "gfm" | "commonmark" | "commonmark_x");
+    let mut format = profile.input_format.clone();
+    for extension in &profile.reader_extensions {
+        // Pandoc's Markdown already supports attributes natively and rejects the CommonMark-only flag
+        if !commonmark && COMMONMARK_ONLY_EXTENSIONS.contains(&extension.as_str()) {
+            continue;
+        }
+        format.push('+');
+        format.push_str(extension);
+    }
+    format
+}
+
+/// Absolute, normalized form of `path`, relative paths being resolved against the working directory.
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    Ok(images::normalize(&std::path::absolute(path)?))
+}
+
+/// Deepest directory containing every given (absolute) path.
+fn common_ancestor(paths: &[PathBuf]) -> PathBuf {
+    let mut ancestor = match paths.first() {
+        Some(first) => first.clone(),
+        None => return PathBuf::from("/"),
+    };
+    for path in &paths[1..] {
+        while !path.starts_with(&ancestor) {
+            match ancestor.parent() {
+                Some(parent) => ancestor = parent.to_path_buf(),
+                None => return Path::new("/").to_path_buf(),
+            }
+        }
+    }
+    ancestor
+}
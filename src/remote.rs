@@ -0,0 +1,163 @@
+use anyhow::{Result, anyhow};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::images;
+
+/// Settings for downloading remote (`http://`, `https://`) images before compilation, since Typst cannot load URLs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteImageOptions {
+    /// Never touch the network: only previously cached images are used.
+    pub offline: bool,
+    /// Substitute a placeholder for images that cannot be fetched instead of failing.
+    pub placeholder: bool,
+    /// Timeout for each download.
+    pub timeout: Duration,
+    /// Maximum size of a single image, in bytes.
+    pub max_bytes: u64,
+    /// Cache directory. Images are stored by content hash, with an index keyed by URL hash.
+    pub cache_dir: PathBuf,
+}
+
+impl Default for RemoteImageOptions {
+    fn default() -> Self {
+        Self {
+            offline: false,
+            placeholder: false,
+            timeout: Duration::from_secs(30),
+            max_bytes: 20 * 1024 * 1024,
+            cache_dir: crate::cache::cache_dir("images"),
+        }
+    }
+}
+
+const PLACEHOLDER_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="320" height="180" viewBox="0 0 320 180">
+  <rect width="320" height="180" fill="#eeeeee" stroke="#999999" stroke-width="2" stroke-dasharray="8 6"/>
+  <text x="160" y="95" font-family="sans-serif" font-size="16" fill="#666666" text-anchor="middle">Image unavailable</text>
+</svg>
+"##;
+
+/// Downloads every remote image into the cache and rewrites the references to the cached files.
+///
+/// Cached images are reused without touching the network. Failures are collected and reported together,
+/// unless `placeholder` is set, in which case the image is replaced by a placeholder.
+pub fn localize_remote_images(markdown: &str, options: &RemoteImageOptions) -> Result<String> {
+    let has_remote = images::find_images(markdown).iter().any(|image| is_remote(&image.url));
+    if !has_remote {
+        return Ok(markdown.to_string());
+    }
+    std::fs::create_dir_all(options.cache_dir.join("index"))?;
+    std::fs::create_dir_all(options.cache_dir.join("objects"))?;
+
+    let agent = ureq::AgentBuilder::new().timeout(options.timeout).build();
+    let mut failures = Vec::new();
+    let rewritten = images::rewrite_images(markdown, |image| {
+        if !is_remote(&image.url) {
+            return None;
+        }
+        let result = match cached(&image.url, options) {
+            Some(path) => Ok(path),
+            None if options.offline => Err(anyhow!("not cached and --offline is set")),
+            None => download(&agent, &image.url, options),
+        };
+        match result {
+            Ok(path) => Some(path.to_string_lossy().replace('\\', "/")),
+            Err(e) if options.placeholder => {
+                warn!("Using a placeholder for {}: {}", image.url, e);
+                placeholder(options).ok().map(|path| path.to_string_lossy().replace('\\', "/"))
+            }
+            Err(e) => {
+                failures.push(format!("  - {}: {}", image.url, e));
+                None
+            }
+        }
+    });
+
+    if !failures.is_empty() {
+        return Err(anyhow!(
+            "{} remote image(s) could not be fetched:\n{}",
+            failures.len(),
+            failures.join("\n")
+        ));
+    }
+    Ok(rewritten)
+}
+
+fn is_remote(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+fn hex_digest(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn index_path(url: &str, options: &RemoteImageOptions) -> PathBuf {
+    options.cache_dir.join("index").join(hex_digest(url.as_bytes()))
+}
+
+/// Looks up a previously downloaded image through the URL index.
+fn cached(url: &str, options: &RemoteImageOptions) -> Option<PathBuf> {
+    let object = std::fs::read_to_string(index_path(url, options)).ok()?;
+    let path = options.cache_dir.join("objects").join(object.trim());
+    path.exists().then_some(path)
+}
+
+fn download(agent: &ureq::Agent, url: &str, options: &RemoteImageOptions) -> Result<PathBuf> {
+    info!("Downloading {}", url);
+    let response = agent.get(url).call().map_err(|e| anyhow!("{}", e))?;
+    if let Some(length) = response.header("Content-Length").and_then(|l| l.parse::<u64>().ok())
+        && length > options.max_bytes
+    {
+        return Err(anyhow!("image is {} bytes, over the {} byte limit", length, options.max_bytes));
+    }
+    let content_type = response.header("Content-Type").unwrap_or_default().to_string();
+
+    let mut data = Vec::new();
+    response.into_reader().take(options.max_bytes + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > options.max_bytes {
+        return Err(anyhow!("image is over the {} byte limit", options.max_bytes));
+    }
+
+    let extension = extension_from_url(url)
+        .or_else(|| extension_from_content_type(&content_type))
+        .ok_or_else(|| anyhow!("unsupported image type '{}'", content_type))?;
+    let object = format!("{}.{}", hex_digest(&data), extension);
+    let path = options.cache_dir.join("objects").join(&object);
+    if !path.exists() {
+        crate::cache::write_atomic(&path, &data)?;
+    }
+    crate::cache::write_atomic(&index_path(url, options), object.as_bytes())?;
+    debug!("Cached {} as {}", url, path.display());
+    Ok(path)
+}
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp"];
+
+fn extension_from_url(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next()?;
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    IMAGE_EXTENSIONS.iter().find(|e| **e == extension).copied()
+}
+
+fn extension_from_content_type(content_type: &str) -> Option<&'static str> {
+    match content_type.split(';').next()?.trim() {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/svg+xml" => Some("svg"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+fn placeholder(options: &RemoteImageOptions) -> Result<PathBuf> {
+    let path = options.cache_dir.join("placeholder.svg");
+    if !path.exists() {
+        crate::cache::write_atomic(&path, PLACEHOLDER_SVG.as_bytes())?;
+    }
+    Ok(path)
+}
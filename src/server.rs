@@ -1,5 +1,5 @@
 use axum::{
-    extract::Json,
+    extract::{Json, State},
     http::{header, StatusCode, Uri},
     response::{Html, IntoResponse, Response},
     routing::{get, post},
@@ -12,7 +12,8 @@ use tempfile::Builder;
 use tower_http::cors::CorsLayer;
 
 use crate::pandoc::PandocWrapper;
-use crate::styles::Profile;
+use crate::remote::RemoteImageOptions;
+use crate::styles::{CodeTheme, ColumnLayout, DocumentClass, FontOptions, HeadingStyle, OutlineOptions, OutlinePosition, PrettyCode, Profile, Theme};
 use tracing::{debug, error};
 
 #[derive(RustEmbed)]
@@ -24,11 +25,47 @@ pub struct ConvertRequest {
     pub markdown: String,
     pub density: Option<String>,
     pub two_cols: Option<bool>,
+    /// Number of columns (1-6); takes precedence over `two_cols`.
+    pub columns: Option<u8>,
+    /// Cheat-sheet layout, with 3 columns or `columns` when it is 4.
+    pub cheat_sheet: Option<bool>,
+    /// "document" or "slides".
+    pub format: Option<String>,
+    /// A document class ("article", "report", "letter", "memo" or "resume"); empty for the front matter's.
+    pub class: Option<String>,
+    /// A paper size such as "a4", "letter" or "6inx9in".
+    pub paper: Option<String>,
+    pub landscape: Option<bool>,
     pub latex_font: Option<bool>,
+    /// Ruby (furigana) annotations.
+    pub ruby: Option<bool>,
+    /// A font pairing ("academic", "modern-sans", "technical") or a body font family.
+    pub font: Option<String>,
+    /// A built-in theme ("default" or "screen"); theme files are a CLI-only feature.
+    pub theme: Option<String>,
     pub alt_table: Option<bool>,
     pub pretty_code: Option<bool>,
+    pub line_numbers: Option<bool>,
+    pub code_theme: Option<String>,
+    pub callouts: Option<bool>,
+    pub diagrams: Option<bool>,
+    pub crossrefs: Option<bool>,
     pub section_numbering: Option<bool>,
+    pub headings: Option<HeadingStyle>,
     pub outline: Option<bool>,
+    pub outline_position: Option<String>,
+    pub outline_depth: Option<u8>,
+}
+
+/// Server-wide settings shared by the request handlers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServerOptions {
+    /// Download remote images referenced by requests. Off by default, since any client could
+    /// otherwise make the server fetch arbitrary URLs, including internal ones.
+    pub remote_images: bool,
+    /// Render diagram blocks with plantuml, dot and mmdc. Off by default, since their include and image
+    /// directives let clients read local files and make outbound requests.
+    pub diagrams: bool,
 }
 
 #[derive(Serialize)]
@@ -36,17 +73,18 @@ pub struct ConvertResponse {
     pub pdf_base64: String,
 }
 
-pub async fn start_server(port: u16, api_only: bool, allow_external: bool) -> anyhow::Result<()> {
+pub async fn start_server(port: u16, api_only: bool, allow_external: bool, options: ServerOptions) -> anyhow::Result<()> {
     let mut app = Router::new()
         .route("/api/convert", post(handle_convert_pdf))
         .route("/api/convert/typ", post(handle_convert_typ))
+        .route("/api/fonts", get(handle_fonts))
         .route("/api/health", get(|| async { "OK" }));
 
     if !api_only {
         app = app.fallback(static_handler);
     }
 
-    let app = app.layer(CorsLayer::permissive());
+    let app = app.layer(CorsLayer::permissive()).with_state(options);
 
     let host = if allow_external { [0, 0, 0, 0] } else { [127, 0, 0, 1] };
     let addr = SocketAddr::from((host, port));
@@ -67,19 +105,40 @@ pub async fn start_server(port: u16, api_only: bool, allow_external: bool) -> an
     Ok(())
 }
 
-async fn handle_convert_pdf(payload: Json<ConvertRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+async fn handle_fonts() -> Result<impl IntoResponse, (StatusCode, String)> {
+    let families = tokio::task::spawn_blocking(|| {
+        let font_paths: Vec<_> = crate::fonts::embedded_font_dir().ok().flatten().into_iter().collect();
+        crate::fonts::list_fonts(&font_paths, false)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(families))
+}
+
+async fn handle_convert_pdf(
+    State(options): State<ServerOptions>,
+    payload: Json<ConvertRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
     tracing::info!("Received PDF conversion request");
-    handle_convert(payload, true).await
+    handle_convert(options, payload, true).await
 }
 
-async fn handle_convert_typ(payload: Json<ConvertRequest>) -> Result<impl IntoResponse, (StatusCode, String)> {
+async fn handle_convert_typ(
+    State(options): State<ServerOptions>,
+    payload: Json<ConvertRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
     tracing::info!("Received Typst conversion request");
-    handle_convert(payload, false).await
+    handle_convert(options, payload, false).await
 }
 
-async fn handle_convert(Json(payload): Json<ConvertRequest>, is_pdf: bool) -> Result<impl IntoResponse, (StatusCode, String)> {
+async fn handle_convert(
+    options: ServerOptions,
+    Json(payload): Json<ConvertRequest>,
+    is_pdf: bool,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
     let mut profile = Profile::new();
     profile.set_global_defaults();
+    profile.set_conditionals();
     
     if let Some(density) = payload.density {
         profile.set_density(&density);
@@ -87,20 +146,106 @@ async fn handle_convert(Json(payload): Json<ConvertRequest>, is_pdf: bool) -> Re
     if let Some(true) = payload.two_cols {
         profile.set_two_cols(true);
     }
+    if let Some(count) = payload.columns {
+        let layout = ColumnLayout { count: Some(count), ..ColumnLayout::default() };
+        profile.set_columns(&layout).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+    if let Some(paper) = payload.paper.as_deref().filter(|p| !p.is_empty()) {
+        profile.set_paper(paper).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+    if let Some(true) = payload.landscape {
+        profile.set_landscape(true);
+    }
+    profile.set_orientation_blocks();
+    if let Some(true) = payload.cheat_sheet {
+        let columns = payload.columns.filter(|&c| c == 4).unwrap_or(3);
+        profile.set_cheat_sheet(columns).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+    if let Some(format) = &payload.format {
+        profile.set_format(format).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+    if let Some(class) = payload.class.as_deref().filter(|c| !c.is_empty()) {
+        let class = DocumentClass::parse(class).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        profile.set_class(class);
+    }
     if let Some(true) = payload.latex_font {
         profile.set_latex_font();
     }
+    if let Some(true) = payload.ruby {
+        profile.set_ruby();
+    }
+    if let Some(font) = payload.font.filter(|f| !f.is_empty()) {
+        let fonts = if FontOptions::PAIRINGS.contains(&font.as_str()) {
+            FontOptions { pairing: Some(font), ..FontOptions::default() }
+        } else {
+            FontOptions { body: vec![font], ..FontOptions::default() }
+        };
+        profile.set_fonts(&fonts).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+    if let Some(name) = payload.theme.as_deref() {
+        let theme = Theme::named(name).ok_or_else(|| (StatusCode::BAD_REQUEST, format!("Unknown theme '{}'", name)))?;
+        profile.set_theme(&theme).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
     if let Some(true) = payload.alt_table {
         profile.set_alt_table();
     }
     if let Some(true) = payload.pretty_code {
-        profile.set_pretty_code();
+        // Custom theme files are a CLI-only feature
+        let theme = match payload.code_theme.as_deref() {
+            Some("dark") => CodeTheme::Dark,
+            _ => CodeTheme::Light,
+        };
+        profile.set_pretty_code_with(&PrettyCode {
+            line_numbers: payload.line_numbers.unwrap_or(false),
+            theme,
+            ..PrettyCode::default()
+        })
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+    if let Some(true) = payload.callouts {
+        profile.set_callouts();
+    }
+    if let Some(true) = payload.diagrams {
+        if options.diagrams {
+            profile.set_diagrams();
+        } else {
+            tracing::info!("Leaving diagram blocks as code; start the server with --diagrams to render them");
+        }
+    }
+    if let Some(true) = payload.crossrefs {
+        profile.set_crossrefs();
     }
     if let Some(true) = payload.section_numbering {
         profile.set_section_numbering(true);
     }
+    if let Some(headings) = &payload.headings {
+        profile.set_heading_style(headings).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
     if let Some(true) = payload.outline {
-        profile.set_outline();
+        profile.set_outline_with(&OutlineOptions {
+            position: payload.outline_position.as_deref().map(OutlinePosition::parse).unwrap_or_default(),
+            depth: payload.outline_depth,
+            ..OutlineOptions::default()
+        })
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+
+    if options.remote_images {
+        profile.set_remote_images(RemoteImageOptions::default());
+    }
+    profile.set_variable_substitution(false);
+    match crate::fonts::embedded_font_dir() {
+        Ok(Some(dir)) => {
+            let _ = profile.add_font_path(dir);
+        }
+        Ok(None) => {}
+        Err(e) => error!("Failed to unpack embedded fonts: {}", e),
+    }
+
+    // The markdown is written to a temporary directory, so relative paths resolve against
+    // the directory the server was started from, and images are only read from there
+    if let Ok(cwd) = std::env::current_dir() {
+        profile.set_image_sandbox(cwd);
     }
 
     // Create a temporary directory for conversion
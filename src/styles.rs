@@ -1,5 +1,10 @@
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::remote::RemoteImageOptions;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Margin {
@@ -18,10 +23,702 @@ pub struct Metadata {
     pub mainfont: Option<String>,
     #[serde(rename = "section-numbering")]
     pub section_numbering: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bibliography: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub csl: Option<String>,
     #[serde(flatten)]
     pub extra: Mapping,
 }
 
+/// Where the generated outline (TOC) is placed in the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutlinePosition {
+    /// Right after the title block, before the body.
+    Start,
+    /// After the body, following a weak page break.
+    #[default]
+    End,
+}
+
+impl OutlinePosition {
+    /// Parses "start" or "end", falling back to the end of the document.
+    pub fn parse(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "start" | "begin" | "top" => OutlinePosition::Start,
+            _ => OutlinePosition::End,
+        }
+    }
+}
+
+/// Settings for the generated table of contents and the optional lists of figures, tables and listings.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OutlineOptions {
+    pub position: OutlinePosition,
+    /// Maximum heading level to include. `None` includes every level.
+    pub depth: Option<u8>,
+    /// Custom title. `None` uses the `contents` label for `lang`.
+    pub title: Option<String>,
+    /// Indentation of nested entries as a Typst length (e.g. "1.5em"). `None` uses Typst's automatic indent.
+    pub indent: Option<String>,
+    pub list_of_figures: bool,
+    pub list_of_tables: bool,
+    pub list_of_listings: bool,
+}
+
+/// How citations are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CitationMode {
+    /// Pandoc formats citations and the reference list with citeproc (CSL).
+    #[default]
+    Citeproc,
+    /// Citations are handed to Typst's native `cite` and `bibliography`.
+    Typst,
+}
+
+impl CitationMode {
+    /// Parses "citeproc" or "typst", falling back to citeproc.
+    pub fn parse(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "typst" | "native" => CitationMode::Typst,
+            _ => CitationMode::Citeproc,
+        }
+    }
+}
+
+/// A document class: its own title block, page header and footer, and heading rules, built from the templates
+/// in `assets/typst/classes`. Front matter fields the class needs (e.g. a letter's sender and recipient) are
+/// handed to the template as `quoin-fields`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentClass {
+    Article,
+    Report,
+    Letter,
+    Memo,
+    Resume,
+}
+
+impl DocumentClass {
+    pub const NAMES: [&str; 5] = ["article", "report", "letter", "memo", "resume"];
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "article" => Ok(DocumentClass::Article),
+            "report" => Ok(DocumentClass::Report),
+            "letter" => Ok(DocumentClass::Letter),
+            "memo" => Ok(DocumentClass::Memo),
+            "resume" | "cv" => Ok(DocumentClass::Resume),
+            _ => Err(anyhow!(
+                "Unknown document class '{}' (expected {})",
+                name,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DocumentClass::Article => "article",
+            DocumentClass::Report => "report",
+            DocumentClass::Letter => "letter",
+            DocumentClass::Memo => "memo",
+            DocumentClass::Resume => "resume",
+        }
+    }
+
+    /// Front matter fields the class cannot do without.
+    pub fn required_fields(&self) -> &'static [&'static str] {
+        match self {
+            DocumentClass::Article | DocumentClass::Resume => &["title"],
+            DocumentClass::Report => &["title", "author"],
+            DocumentClass::Letter => &["sender", "recipient"],
+            DocumentClass::Memo => &["to", "from", "subject"],
+        }
+    }
+
+    /// Front matter fields read by the template, besides those Pandoc passes to the title block.
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            DocumentClass::Article | DocumentClass::Report => &["title"],
+            DocumentClass::Letter => &["sender", "recipient", "subject", "closing", "signature"],
+            DocumentClass::Memo => &["to", "from", "cc", "subject"],
+            DocumentClass::Resume => &["email", "phone", "location", "website"],
+        }
+    }
+
+    /// Typst source defining `quoin-title-block` and setting the class's page and heading rules.
+    pub fn template(&self) -> String {
+        let template = match self {
+            DocumentClass::Article => include_str!("assets/typst/classes/article.typ"),
+            DocumentClass::Report => include_str!("assets/typst/classes/report.typ"),
+            DocumentClass::Letter => include_str!("assets/typst/classes/letter.typ"),
+            DocumentClass::Memo => include_str!("assets/typst/classes/memo.typ"),
+            DocumentClass::Resume => include_str!("assets/typst/classes/resume.typ"),
+        };
+        format!("{}{}", include_str!("assets/typst/classes/common.typ"), template)
+    }
+
+    /// The `quoin-fields` dictionary read by the template, from the document's metadata. Fails when a required
+    /// field is missing.
+    pub fn fields_prelude(&self, metadata: &Mapping) -> Result<String> {
+        let missing: Vec<&str> = self
+            .required_fields()
+            .iter()
+            .copied()
+            .filter(|field| metadata.get(*field).is_none_or(Value::is_null))
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "The {} class requires these front matter fields: {}",
+                self.name(),
+                missing.join(", ")
+            ));
+        }
+        let fields: Vec<String> = self
+            .fields()
+            .iter()
+            .map(|field| format!("{}: {}", field, typst_field(metadata.get(*field))))
+            .collect();
+        Ok(format!("#let quoin-fields = ({})\n", fields.join(", ")))
+    }
+}
+
+/// Syntax highlighting theme for code blocks.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum CodeTheme {
+    /// Typst's default highlighting on a light background.
+    #[default]
+    Light,
+    /// The bundled "Quoin Dark" highlighting on a dark background.
+    Dark,
+    /// A custom `.tmTheme` file, on a light background.
+    Custom(PathBuf),
+}
+
+impl CodeTheme {
+    /// Parses "light" or "dark"; anything else is taken as the path of a `.tmTheme` file.
+    pub fn parse(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "light" | "default" => CodeTheme::Light,
+            "dark" => CodeTheme::Dark,
+            _ => CodeTheme::Custom(PathBuf::from(value)),
+        }
+    }
+}
+
+/// Code block styling options. Blocks can override them with attributes such as `linenos=true`,
+/// `hl_lines="3-5"`, `title="src/main.rs"`, `startFrom=10` and `wrap=false`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrettyCode {
+    /// Number the lines of every code block.
+    pub line_numbers: bool,
+    pub theme: CodeTheme,
+    /// Wrap long lines instead of letting them overflow (clipped at the block edge).
+    pub wrap: bool,
+}
+
+impl Default for PrettyCode {
+    fn default() -> Self {
+        Self {
+            line_numbers: false,
+            theme: CodeTheme::Light,
+            wrap: true,
+        }
+    }
+}
+
+/// Font families per role, most preferred first. Later families are fallbacks, used when a font is missing
+/// or lacks a glyph.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FontOptions {
+    /// Named pairing providing every role: "academic", "modern-sans" or "technical".
+    /// Roles set explicitly take precedence.
+    pub pairing: Option<String>,
+    pub body: Vec<String>,
+    pub heading: Vec<String>,
+    pub mono: Vec<String>,
+    pub math: Vec<String>,
+}
+
+impl FontOptions {
+    pub const PAIRINGS: &[&str] = &["academic", "modern-sans", "technical"];
+
+    pub fn pairing(name: &str) -> Option<Self> {
+        let families = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let (body, heading, mono, math): (&[&str], &[&str], &[&str], &[&str]) = match name {
+            "academic" => (
+                &["New Computer Modern", "Libertinus Serif"],
+                &["New Computer Modern", "Libertinus Serif"],
+                &["New Computer Modern Mono", "DejaVu Sans Mono"],
+                &["New Computer Modern Math"],
+            ),
+            "modern-sans" => (
+                &["Inter", "Source Sans 3", "Noto Sans", "DejaVu Sans"],
+                &["Inter", "Source Sans 3", "Noto Sans", "DejaVu Sans"],
+                &["JetBrains Mono", "Fira Code", "DejaVu Sans Mono"],
+                &["Fira Math", "New Computer Modern Math"],
+            ),
+            "technical" => (
+                &["IBM Plex Sans", "Source Sans 3", "DejaVu Sans"],
+                &["IBM Plex Sans", "Source Sans 3", "DejaVu Sans"],
+                &["IBM Plex Mono", "Source Code Pro", "DejaVu Sans Mono"],
+                &["STIX Two Math", "New Computer Modern Math"],
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            pairing: Some(name.to_string()),
+            body: families(body),
+            heading: families(heading),
+            mono: families(mono),
+            math: families(math),
+        })
+    }
+
+    /// Expands the pairing into its roles, keeping the roles set explicitly.
+    pub fn resolved(&self) -> Result<Self> {
+        let mut fonts = match &self.pairing {
+            Some(name) => Self::pairing(name).ok_or_else(|| {
+                anyhow!("Unknown font pairing '{}' (available: {})", name, Self::PAIRINGS.join(", "))
+            })?,
+            None => Self::default(),
+        };
+        fonts.merge(self);
+        Ok(fonts)
+    }
+
+    /// Roles and their families, in a fixed order.
+    pub fn roles(&self) -> [(&'static str, &[String]); 4] {
+        [("body", &self.body), ("heading", &self.heading), ("mono", &self.mono), ("math", &self.math)]
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (target, source) in [
+            (&mut self.body, &other.body),
+            (&mut self.heading, &other.heading),
+            (&mut self.mono, &other.mono),
+            (&mut self.math, &other.math),
+        ] {
+            if !source.is_empty() {
+                *target = source.clone();
+            }
+        }
+    }
+}
+
+/// Paragraph and text settings, emitted as Typst `set par(...)` and `set text(...)` rules.
+/// `None` keeps the current setting.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Typography {
+    /// Space between the lines of a paragraph, e.g. "0.65em".
+    pub leading: Option<String>,
+    /// Space between paragraphs, e.g. "1.2em".
+    pub paragraph_spacing: Option<String>,
+    /// Indent of the first line of each paragraph but the first, e.g. "1.5em".
+    pub first_line_indent: Option<String>,
+    /// Justified (`true`) or ragged-right (`false`) text.
+    pub justify: Option<bool>,
+    /// Hyphenate words, using the patterns of the document language. Typst's default hyphenates justified text.
+    pub hyphenate: Option<bool>,
+    /// Avoid widows and orphans (single lines left at the top or bottom of a page or column).
+    pub widow_orphan_control: Option<bool>,
+}
+
+impl Typography {
+    /// Defaults for a density level: tighter leading and spacing for dense layouts, looser for comfort.
+    /// "standard" keeps Typst's defaults.
+    pub fn for_density(level: &str) -> Self {
+        let (leading, spacing) = match level {
+            "ultra-dense" => ("0.45em", "0.75em"),
+            "dense" => ("0.55em", "1em"),
+            "comfort" => ("0.8em", "1.4em"),
+            _ => return Self::default(),
+        };
+        Self {
+            leading: Some(leading.to_string()),
+            paragraph_spacing: Some(spacing.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Typst rules for the settings that are set.
+    pub fn rules(&self) -> Result<Vec<String>> {
+        let mut par = Vec::new();
+        for (name, value) in [
+            ("leading", &self.leading),
+            ("spacing", &self.paragraph_spacing),
+            ("first-line-indent", &self.first_line_indent),
+        ] {
+            if let Some(value) = value {
+                par.push(format!("{}: {}", name, typst_length(value)?));
+            }
+        }
+        if let Some(justify) = self.justify {
+            par.push(format!("justify: {}", justify));
+        }
+
+        let mut rules = Vec::new();
+        if !par.is_empty() {
+            rules.push(format!("#set par({})", par.join(", ")));
+        }
+        if let Some(hyphenate) = self.hyphenate {
+            rules.push(format!("#set text(hyphenate: {})", hyphenate));
+        }
+        if let Some(control) = self.widow_orphan_control {
+            let cost = if control { "100%" } else { "0%" };
+            rules.push(format!("#set text(costs: (widow: {}, orphan: {}))", cost, cost));
+        }
+        Ok(rules)
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        macro_rules! take {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field.clone();
+                })*
+            };
+        }
+        take!(leading, paragraph_spacing, first_line_indent, justify, hyphenate, widow_orphan_control);
+    }
+
+    /// Unsets the fields that still have the value `other` gave them.
+    fn unmerge(&mut self, other: &Self) {
+        macro_rules! reset {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() && self.$field == other.$field {
+                    self.$field = None;
+                })*
+            };
+        }
+        reset!(leading, paragraph_spacing, first_line_indent, justify, hyphenate, widow_orphan_control);
+    }
+}
+
+/// Heading numbering and appearance. Per-level lists start at level 1; `None` and empty lists keep the
+/// current setting.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HeadingStyle {
+    /// Typst numbering pattern, e.g. "1.1", "1.a", "I.1" or "A.1".
+    pub numbering: Option<String>,
+    /// Numbering pattern from the first `{.appendix}` heading on, e.g. "A.1". The counter restarts there.
+    pub appendix: Option<String>,
+    /// Text size per level, e.g. ["18pt", "14pt", "12pt"].
+    pub sizes: Vec<String>,
+    /// Font weight per level: "regular", "medium", "semibold", "bold", ... or 100 to 900.
+    pub weights: Vec<String>,
+    /// Text color per level: "#1f4e79" or a Typst color name such as "navy".
+    pub colors: Vec<String>,
+    /// Space above headings.
+    pub above: Option<String>,
+    /// Space below headings.
+    pub below: Option<String>,
+    /// Start every level-1 heading on a new page.
+    pub page_break: Option<bool>,
+    /// Keep headings on the same page or column as the paragraph that follows.
+    pub keep_with_next: Option<bool>,
+}
+
+impl HeadingStyle {
+    /// Typst rules for the settings that are set.
+    pub fn rules(&self) -> Result<Vec<String>> {
+        let mut rules = Vec::new();
+        if let Some(numbering) = &self.numbering {
+            rules.push(format!("#set heading(numbering: {})", typst_str(numbering)));
+        }
+        let levels = self.sizes.len().max(self.weights.len()).max(self.colors.len());
+        for level in 0..levels {
+            let mut text = Vec::new();
+            if let Some(size) = self.sizes.get(level) {
+                text.push(format!("size: {}", typst_length(size)?));
+            }
+            if let Some(weight) = self.weights.get(level) {
+                text.push(format!("weight: {}", typst_weight(weight)?));
+            }
+            if let Some(color) = self.colors.get(level) {
+                text.push(format!("fill: {}", typst_color(color)?));
+            }
+            if !text.is_empty() {
+                rules.push(format!("#show heading.where(level: {}): set text({})", level + 1, text.join(", ")));
+            }
+        }
+        let mut block = Vec::new();
+        if let Some(above) = &self.above {
+            block.push(format!("above: {}", typst_length(above)?));
+        }
+        if let Some(below) = &self.below {
+            block.push(format!("below: {}", typst_length(below)?));
+        }
+        if let Some(keep) = self.keep_with_next {
+            block.push(format!("sticky: {}", keep));
+        }
+        if !block.is_empty() {
+            rules.push(format!("#show heading: set block({})", block.join(", ")));
+        }
+        if self.page_break == Some(true) {
+            rules.push("#show heading.where(level: 1): it => { pagebreak(weak: true); it }".to_string());
+        }
+        Ok(rules)
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (target, source) in [
+            (&mut self.sizes, &other.sizes),
+            (&mut self.weights, &other.weights),
+            (&mut self.colors, &other.colors),
+        ] {
+            if !source.is_empty() {
+                *target = source.clone();
+            }
+        }
+        for (target, source) in [
+            (&mut self.numbering, &other.numbering),
+            (&mut self.appendix, &other.appendix),
+            (&mut self.above, &other.above),
+            (&mut self.below, &other.below),
+        ] {
+            if source.is_some() {
+                *target = source.clone();
+            }
+        }
+        self.page_break = other.page_break.or(self.page_break);
+        self.keep_with_next = other.keep_with_next.or(self.keep_with_next);
+    }
+}
+
+/// Colors shared by quoin's styles (headings, links, tables, code, callouts) and an optional logo.
+///
+/// Loaded from TOML; `None` keeps the current value. Every profile starts from the built-in "default" theme.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Heading color.
+    pub primary: Option<String>,
+    /// Horizontal rules.
+    pub accent: Option<String>,
+    /// Link text.
+    pub link: Option<String>,
+    /// Body text.
+    pub text: Option<String>,
+    /// Page background.
+    pub background: Option<String>,
+    /// Fill of table header rows (with alternative table styling).
+    pub table_header: Option<String>,
+    /// Table borders (with alternative table styling).
+    pub table_stroke: Option<String>,
+    /// Code block background (with pretty code and the light code theme).
+    pub code_background: Option<String>,
+    /// Inline code background (with pretty code).
+    pub code_inline: Option<String>,
+    /// Callout colors, by kind.
+    pub callouts: CalloutColors,
+    /// Dark background: callout boxes are tinted darker instead of lighter.
+    pub dark: Option<bool>,
+    /// Image shown in the page header, e.g. "brand/logo.svg".
+    pub logo: Option<PathBuf>,
+    /// Logo width (default 2.5cm).
+    pub logo_width: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CalloutColors {
+    pub note: Option<String>,
+    pub tip: Option<String>,
+    pub important: Option<String>,
+    pub warning: Option<String>,
+    pub caution: Option<String>,
+}
+
+impl Theme {
+    /// Names accepted by `Theme::named`.
+    pub const BUILTIN: &'static [&'static str] = &["default", "screen"];
+
+    /// A built-in theme: "default" (print) or "screen" (light text on a dark page).
+    pub fn named(name: &str) -> Option<Self> {
+        let source = match name {
+            "default" => include_str!("assets/themes/default.toml"),
+            "screen" => include_str!("assets/themes/screen.toml"),
+            _ => return None,
+        };
+        Some(toml::from_str(source).expect("built-in theme is valid"))
+    }
+
+    /// Parses a theme file; its logo path is relative to the file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read theme {}: {}", path.display(), e))?;
+        let mut theme: Theme =
+            toml::from_str(&content).map_err(|e| anyhow!("Invalid theme {}: {}", path.display(), e))?;
+        if let Some(logo) = &theme.logo {
+            let dir = std::path::absolute(path)?.parent().map(Path::to_path_buf).unwrap_or_default();
+            theme.logo = Some(dir.join(logo));
+        }
+        Ok(theme)
+    }
+
+    /// A built-in theme name or the path to a theme file.
+    pub fn resolve(spec: &str) -> Result<Self> {
+        if let Some(theme) = Self::named(spec) {
+            return Ok(theme);
+        }
+        let path = Path::new(spec);
+        if path.is_file() {
+            return Self::load(path);
+        }
+        Err(anyhow!("Unknown theme '{}' (built-in: {}; or a .toml file)", spec, Self::BUILTIN.join(", ")))
+    }
+
+    /// The `quoin-theme` dictionary the other includes read their colors from, and the page-level rules
+    /// (background, logo). Written at the top of the header.
+    pub fn prelude(&self) -> Result<String> {
+        let color = |value: &Option<String>| value.as_deref().map_or(Ok("none".to_string()), typst_color);
+        let callouts = &self.callouts;
+        let callout_fill = if self.dark == Some(true) { "c => c.darken(75%)" } else { "c => c.lighten(92%)" };
+        let mut prelude = format!(
+            "#let quoin-theme = (\n  primary: {}, accent: {}, link: {}, text: {}, background: {},\n  \
+             table-header: {}, table-stroke: {}, code-background: {}, code-inline: {},\n  \
+             callouts: (note: {}, tip: {}, important: {}, warning: {}, caution: {}),\n  callout-fill: {},\n)\n",
+            color(&self.primary)?,
+            color(&self.accent)?,
+            color(&self.link)?,
+            color(&self.text)?,
+            color(&self.background)?,
+            color(&self.table_header)?,
+            color(&self.table_stroke)?,
+            color(&self.code_background)?,
+            color(&self.code_inline)?,
+            color(&callouts.note)?,
+            color(&callouts.tip)?,
+            color(&callouts.important)?,
+            color(&callouts.warning)?,
+            color(&callouts.caution)?,
+            callout_fill,
+        );
+        if let Some(background) = &self.background {
+            prelude.push_str(&format!("#set page(fill: {})\n", typst_color(background)?));
+        }
+        if let Some(logo) = &self.logo {
+            let width = typst_length(self.logo_width.as_deref().unwrap_or("2.5cm"))?;
+            prelude.push_str(&format!(
+                "#set page(header: align(end, image({}, width: {})))\n",
+                typst_path(logo),
+                width
+            ));
+        }
+        Ok(prelude)
+    }
+
+    /// Typst rules for the text-level colors that are set.
+    pub fn rules(&self) -> Result<Vec<String>> {
+        let mut rules = Vec::new();
+        if let Some(text) = &self.text {
+            rules.push(format!("#set text(fill: {})", typst_color(text)?));
+        }
+        if let Some(primary) = &self.primary {
+            rules.push(format!("#show heading: set text(fill: {})", typst_color(primary)?));
+        }
+        if let Some(link) = &self.link {
+            rules.push(format!("#show link: set text(fill: {})", typst_color(link)?));
+        }
+        Ok(rules)
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        macro_rules! take {
+            ($($field:ident).+) => {
+                if other.$($field).+.is_some() {
+                    self.$($field).+ = other.$($field).+.clone();
+                }
+            };
+        }
+        take!(primary);
+        take!(accent);
+        take!(link);
+        take!(text);
+        take!(background);
+        take!(table_header);
+        take!(table_stroke);
+        take!(code_background);
+        take!(code_inline);
+        take!(callouts.note);
+        take!(callouts.tip);
+        take!(callouts.important);
+        take!(callouts.warning);
+        take!(callouts.caution);
+        take!(dark);
+        take!(logo);
+        take!(logo_width);
+    }
+}
+
+/// Multi-column layout. The body is split into column sections at spanning elements (`{.span}` headings,
+/// `::: span` blocks, wide tables), which run across the full width where they appear. `None` keeps the current
+/// setting.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ColumnLayout {
+    /// Number of columns, 1 to 6.
+    pub count: Option<u8>,
+    /// Space between columns (default 0.5cm).
+    pub gutter: Option<String>,
+    /// Draw a line between columns, in the theme's accent color.
+    pub rule: Option<bool>,
+    /// Give the columns of each section equal heights, so the last page is balanced.
+    pub balance: Option<bool>,
+    /// Let tables too wide for a column span the full width (default true).
+    pub span_wide: Option<bool>,
+}
+
+impl ColumnLayout {
+    pub fn count(&self) -> u8 {
+        self.count.unwrap_or(1)
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        macro_rules! take {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field.clone();
+                })*
+            };
+        }
+        take!(count, gutter, rule, balance, span_wide);
+    }
+}
+
+/// Slide deck settings. `None` keeps the current setting.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SlideOptions {
+    /// Reveal every list one item at a time, not just those in `::: incremental` blocks.
+    pub incremental: Option<bool>,
+    /// Print the `::: notes` of each slide on a page after it, instead of keeping them as `<quoin-notes>` metadata.
+    pub speaker_notes: Option<bool>,
+}
+
+impl SlideOptions {
+    pub fn merge(&mut self, other: &Self) {
+        if other.incremental.is_some() {
+            self.incremental = other.incremental;
+        }
+        if other.speaker_notes.is_some() {
+            self.speaker_notes = other.speaker_notes;
+        }
+    }
+}
+
+/// A file referenced by absolute path from the Typst includes (e.g. a highlighting theme).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupportFile {
+    pub path: PathBuf,
+    /// Content written to `path` before conversion. `None` for existing, user-provided files.
+    pub content: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 /// Represents a document conversion profile, holding all style and layout settings.
 pub struct Profile {
@@ -29,10 +726,83 @@ pub struct Profile {
     pub metadata: Metadata,
     /// Raw Typst code snippets to be included in the document header.
     pub header_includes: Vec<String>,
+    /// Raw Typst code snippets to be inserted at the start of the body, right after the title block.
+    pub before_body_includes: Vec<String>,
     /// Raw Typst code snippets to be appended after the document body.
     pub after_body_includes: Vec<String>,
     /// Whether to use the custom Lua filter for better table dimension handling.
     pub use_lua_table_filter: bool,
+    /// Lua filter sources run by Pandoc, in order, after the table filter.
+    pub lua_filters: Vec<String>,
+    /// Pandoc reader format. GFM unless a feature needs Pandoc's own Markdown (e.g. citations).
+    pub input_format: String,
+    /// Pandoc reader extensions enabled on top of the input format (e.g. "fenced_divs").
+    pub reader_extensions: Vec<String>,
+    /// Cache directory for rendered diagrams. Set when diagram rendering is enabled.
+    pub diagram_cache_dir: Option<PathBuf>,
+    /// Citation processing mode. Set when citations are enabled.
+    pub citations: Option<CitationMode>,
+    /// Extra directories searched for images and other resources, after the input's directory
+    /// and the working directory.
+    pub resource_paths: Vec<PathBuf>,
+    /// When set, images are only read from this directory, and resource paths are ignored.
+    pub image_sandbox: Option<PathBuf>,
+    /// Warn about local images that cannot be found, rather than failing with the list of them.
+    pub allow_missing_images: bool,
+    /// Download settings for remote images. `None` leaves remote images untouched.
+    pub remote_images: Option<RemoteImageOptions>,
+    /// Files referenced from the includes; they are written if needed and kept inside the Typst root.
+    pub support_files: Vec<SupportFile>,
+    /// Expand `!include` lines and `include=` code blocks. Off for a new profile, since it reads arbitrary
+    /// files; the CLI turns it on unless `--no-includes` is given.
+    pub expand_includes: bool,
+    /// Density level set with `set_density` ("standard" by default).
+    pub density: String,
+    /// Paragraph settings, starting from the density level's defaults.
+    pub typography: Typography,
+    /// The before-body rules of the current density level, replaced when the level changes.
+    pub density_rules: Option<String>,
+    /// The outline from `set_outline_with`, in the before- or after-body includes; replaced when set again.
+    pub outline_include: Option<String>,
+    /// Heading settings from `set_heading_style`.
+    pub headings: HeadingStyle,
+    /// Fonts set with `set_fonts`, checked against the fonts Typst can see before conversion.
+    pub fonts: FontOptions,
+    /// Colors and logo, written as the `quoin-theme` dictionary at the top of the header.
+    pub theme: Theme,
+    /// Custom page width and height, set with `set_paper("6inx9in")`. Named sizes go to `metadata.papersize`.
+    pub page_size: Option<(String, String)>,
+    /// Landscape orientation for the whole document.
+    pub landscape: bool,
+    /// Typst source defining `quoin-title-block(title:, subtitle:, authors:, date:, abstract-title:, abstract:)`,
+    /// which replaces Pandoc's title block. `None` keeps Pandoc's.
+    pub title_block: Option<String>,
+    /// Column settings from `set_columns`. Columns are laid out by quoin, so `metadata.columns` stays at 1.
+    pub columns: ColumnLayout,
+    /// Slide deck settings from `set_slides`. `None` for documents.
+    pub slides: Option<SlideOptions>,
+    /// Document class from `set_class`. `None` keeps the generic layout, unless the front matter names one.
+    pub class: Option<DocumentClass>,
+    /// Label overrides from `set_labels` per language, on top of the built-in catalog for `metadata.lang`.
+    pub labels: BTreeMap<String, BTreeMap<String, String>>,
+    /// Extra font directories passed to Typst.
+    pub font_paths: Vec<PathBuf>,
+    /// Only use fonts from `font_paths` and those embedded in Typst, for builds that do not depend on the machine.
+    pub ignore_system_fonts: bool,
+    /// User-provided Lua filter files, run after `lua_filters`.
+    pub lua_filter_files: Vec<PathBuf>,
+    /// Values for `{{name}}` placeholders in the body, from `-V` and config values.
+    pub variables: BTreeMap<String, String>,
+    /// Substitute `{{name}}` placeholders in the body.
+    pub substitute_variables: bool,
+    /// Fail on undefined placeholders instead of leaving them as-is.
+    pub strict_variables: bool,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Profile {
@@ -48,14 +818,48 @@ impl Profile {
             columns: 1,
             mainfont: None,
             section_numbering: None,
+            bibliography: Vec::new(),
+            csl: None,
             extra: Mapping::new(),
         };
 
         Self { 
             metadata,
             header_includes: Vec::new(),
+            before_body_includes: Vec::new(),
             after_body_includes: Vec::new(),
             use_lua_table_filter: true,
+            lua_filters: Vec::new(),
+            input_format: "gfm".to_string(),
+            reader_extensions: Vec::new(),
+            diagram_cache_dir: None,
+            citations: None,
+            resource_paths: Vec::new(),
+            image_sandbox: None,
+            allow_missing_images: false,
+            remote_images: None,
+            support_files: Vec::new(),
+            expand_includes: false,
+            density: "standard".to_string(),
+            typography: Typography::default(),
+            density_rules: None,
+            outline_include: None,
+            headings: HeadingStyle::default(),
+            fonts: FontOptions::default(),
+            theme: Theme::named("default").unwrap_or_default(),
+            page_size: None,
+            landscape: false,
+            title_block: None,
+            columns: ColumnLayout::default(),
+            slides: None,
+            class: None,
+            labels: BTreeMap::new(),
+            font_paths: Vec::new(),
+            ignore_system_fonts: false,
+            lua_filter_files: Vec::new(),
+            variables: BTreeMap::new(),
+            substitute_variables: false,
+            strict_variables: false,
         }
     }
 
@@ -64,6 +868,22 @@ impl Profile {
     /// Supported levels: "ultra-dense", "dense", "standard", "comfort".
     pub fn set_density(&mut self, level: &str) {
         tracing::debug!("Setting density to {}", level);
+        // Replace the previous level's rules and values; settings made since then are kept
+        self.typography.unmerge(&Typography::for_density(&self.density));
+        if let Some(rules) = self.density_rules.take()
+            && let Some(index) = self.before_body_includes.iter().position(|r| *r == rules)
+        {
+            self.before_body_includes.remove(index);
+        }
+        self.density = level.to_lowercase();
+        let typography = Typography::for_density(&self.density);
+        let rules = typography.rules().expect("built-in values are valid lengths");
+        if !rules.is_empty() {
+            let rules = rules.join("\n");
+            self.before_body_includes.push(rules.clone());
+            self.density_rules = Some(rules);
+        }
+        self.typography.merge(&typography);
         match level.to_lowercase().as_str() {
             "ultra-dense" => {
                 self.metadata.fontsize = "8pt".to_string();
@@ -94,20 +914,368 @@ impl Profile {
         }
     }
 
+    /// Set leading, paragraph spacing, indent, justification, hyphenation and widow/orphan control.
+    /// Unset fields keep their current value, so this can be layered on top of the density defaults.
+    pub fn set_typography(&mut self, typography: &Typography) -> Result<()> {
+        tracing::debug!("Setting typography: {:?}", typography);
+        let rules = typography.rules()?;
+        // After the template's own `set par`, hence before-body
+        if !rules.is_empty() {
+            self.before_body_includes.push(rules.join("\n"));
+        }
+        self.typography.merge(typography);
+        Ok(())
+    }
+
     pub fn set_two_cols(&mut self, enabled: bool) {
         tracing::debug!("Setting two columns: {}", enabled);
-        if enabled {
-            self.metadata.columns = 2;
+        let count = if enabled { 2 } else { 1 };
+        self.set_columns(&ColumnLayout { count: Some(count), ..ColumnLayout::default() })
+            .expect("1 and 2 columns are valid");
+    }
+
+    /// Set the number of columns, gutter, column rule, balancing and automatic spanning of wide tables.
+    pub fn set_columns(&mut self, layout: &ColumnLayout) -> Result<()> {
+        tracing::debug!("Setting columns: {:?}", layout);
+        if let Some(count) = layout.count
+            && !(1..=6).contains(&count)
+        {
+            return Err(anyhow!("Invalid column count {} (expected 1 to 6)", count));
+        }
+        let mut layout = layout.clone();
+        if let Some(gutter) = &layout.gutter {
+            layout.gutter = Some(typst_length(gutter)?);
+        }
+        self.columns.merge(&layout);
+        self.metadata.columns = 1;
+        if self.columns.count() > 1 {
+            // Before the filters that unwrap callouts and orientation blocks into raw markup, so the sections
+            // never split them; after conditionals, so dropped blocks are gone
+            let columns_filter = include_str!("assets/lua/columns.lua");
+            if !self.lua_filters.iter().any(|f| f == columns_filter) {
+                let conditionals = include_str!("assets/lua/conditionals.lua");
+                let position = self.lua_filters.iter().position(|f| f == conditionals).map_or(0, |i| i + 1);
+                self.lua_filters.insert(position, columns_filter.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether level-1 headings start a new page (`--chapter-page-breaks` or the report class). Typst only
+    /// breaks pages at the top level, so the column sections filter lets these headings span.
+    pub fn level_one_page_breaks(&self) -> bool {
+        self.headings.page_break == Some(true) || self.class == Some(DocumentClass::Report)
+    }
+
+    /// Cheat-sheet mode: landscape, 3 or 4 columns, ultra-dense text with 1cm margins, every H2 section in a
+    /// color-coded box that stays in one column, compact lists and tables, H1 headings across all columns and
+    /// a title bar in place of the title block.
+    pub fn set_cheat_sheet(&mut self, columns: u8) -> Result<()> {
+        tracing::debug!("Enabling cheat-sheet mode with {} columns", columns);
+        if !(3..=4).contains(&columns) {
+            return Err(anyhow!("Cheat sheets have 3 or 4 columns, not {}", columns));
+        }
+        self.set_density("ultra-dense");
+        self.metadata.margin = Margin { x: "1cm".to_string(), y: "1cm".to_string() };
+        self.set_landscape(true);
+        self.set_columns(&ColumnLayout {
+            count: Some(columns),
+            gutter: Some("0.8em".to_string()),
+            span_wide: Some(false),
+            ..ColumnLayout::default()
+        })?;
+
+        let sections_filter = include_str!("assets/lua/cheat_sheet.lua");
+        if !self.lua_filters.iter().any(|f| f == sections_filter) {
+            // Sections are grouped before the column filter, so it keeps them whole and sees the spanning H1s
+            let columns_filter = include_str!("assets/lua/columns.lua");
+            let position = self.lua_filters.iter().position(|f| f == columns_filter).unwrap_or(0);
+            self.lua_filters.insert(position, sections_filter.to_string());
+            let boxes_filter = include_str!("assets/lua/cheat_boxes.lua");
+            self.lua_filters.push(boxes_filter.to_string());
+            self.header_includes.push(include_str!("assets/typst/cheat_sheet.typ").to_string());
+            self.title_block = Some(include_str!("assets/typst/cheat_title.typ").to_string());
+            self.enable_reader_extension("attributes");
+        }
+        Ok(())
+    }
+
+    /// Use a document class: its title block, page header and footer, and heading rules replace the defaults.
+    /// Its front matter fields are checked and passed to the template at conversion.
+    pub fn set_class(&mut self, class: DocumentClass) {
+        tracing::debug!("Setting document class to {}", class.name());
+        self.class = Some(class);
+        self.title_block = Some(class.template());
+        let closing = "#quoin-letter-closing()";
+        self.after_body_includes.retain(|include| include != closing);
+        if class == DocumentClass::Letter {
+            self.after_body_includes.push(closing.to_string());
+        }
+    }
+
+    /// Override generated strings (outline titles, figure supplements, callout titles, "Page X of Y", ...) for
+    /// one language: "fr" for every French document, "fr-CA" for Canadian French only. See `labels::KEYS` for
+    /// the names.
+    pub fn set_labels(&mut self, lang: &str, labels: &BTreeMap<String, String>) -> Result<()> {
+        tracing::debug!("Setting labels for {}: {:?}", lang, labels);
+        crate::labels::validate(labels)?;
+        if lang.is_empty() || !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(anyhow!("Invalid language '{}' for labels (expected e.g. en, fr or fr-CA)", lang));
+        }
+        let overrides = self.labels.entry(lang.to_lowercase().replace('_', "-")).or_default();
+        overrides.extend(labels.iter().map(|(key, value)| (key.clone(), value.clone())));
+        Ok(())
+    }
+
+    /// The `quoin-labels` dictionary and direction settings for the document language.
+    pub fn labels_prelude(&self) -> String {
+        crate::labels::prelude(&self.metadata.lang, &self.labels)
+    }
+
+    /// Typst rules for Chinese, Japanese and Korean text, or `None` for other languages. The CJK families for
+    /// `metadata.lang` follow the Latin body font, which Typst's default restricts to Latin letters so CJK
+    /// punctuation comes from the CJK font. CJK and Latin text get a thin space between them. Paragraphs are
+    /// justified with looser leading and a first-line indent of two characters in Chinese and one in Japanese,
+    /// unless the typography sets them.
+    pub fn cjk_rules(&self) -> Option<String> {
+        let (serif, sans) = crate::fonts::cjk_families(&self.metadata.lang)?;
+        let quote = |families: &[String]| families.iter().map(|f| typst_str(f)).collect::<Vec<_>>();
+        let cjk = |families: &[&str]| families.iter().map(|f| typst_str(f)).collect::<Vec<_>>();
+        let latin = if !self.fonts.body.is_empty() {
+            quote(&self.fonts.body)
+        } else if let Some(mainfont) = &self.metadata.mainfont {
+            vec![typst_str(mainfont)]
         } else {
-            self.metadata.columns = 1;
+            vec!["(name: \"Libertinus Serif\", covers: \"latin-in-cjk\")".to_string()]
+        };
+        let mut rules =
+            vec![format!("#set text(font: ({},), cjk-latin-spacing: auto)", [latin, cjk(serif)].concat().join(", "))];
+        if !self.fonts.heading.is_empty() {
+            let heading = [quote(&self.fonts.heading), cjk(sans)].concat();
+            rules.push(format!("#show heading: set text(font: ({},))", heading.join(", ")));
+        }
+        // The code font: configured fonts, then `-V monofont` (or `codefont`), then a font with wide coverage
+        let mono = if !self.fonts.mono.is_empty() {
+            quote(&self.fonts.mono)
+        } else if let Some(font) =
+            ["monofont", "codefont"].iter().find_map(|key| self.metadata.extra.get(*key).and_then(Value::as_str))
+        {
+            vec![typst_str(font)]
+        } else {
+            vec![typst_str("DejaVu Sans Mono")]
+        };
+        rules.push(format!("#show raw: set text(font: ({},))", [mono, cjk(sans)].concat().join(", ")));
+
+        let mut par = Vec::new();
+        if self.typography.leading.is_none() {
+            par.push("leading: 0.9em".to_string());
+        }
+        if self.typography.justify.is_none() {
+            par.push("justify: true".to_string());
+        }
+        let indent = match crate::labels::primary(&self.metadata.lang).as_str() {
+            "zh" => Some("2em"),
+            "ja" => Some("1em"),
+            _ => None,
+        };
+        if let Some(indent) = indent
+            && self.typography.first_line_indent.is_none()
+        {
+            par.push(format!("first-line-indent: {}", indent));
+        }
+        if !par.is_empty() {
+            rules.push(format!("#set par({})", par.join(", ")));
+        }
+        Some(rules.join("\n"))
+    }
+
+    /// Ruby (furigana) annotations: `{漢字|かんじ}`, `{漢字|かん|じ}` (one reading per character) or
+    /// `[漢字]{.ruby rt="かんじ"}`.
+    pub fn set_ruby(&mut self) {
+        tracing::debug!("Enabling ruby annotations");
+        let ruby_filter = include_str!("assets/lua/ruby.lua");
+        if !self.lua_filters.iter().any(|f| f == ruby_filter) {
+            self.lua_filters.push(ruby_filter.to_string());
+            self.header_includes.push(include_str!("assets/typst/ruby.typ").to_string());
+            self.enable_reader_extension("bracketed_spans");
+            self.enable_reader_extension("attributes");
+        }
+    }
+
+    /// Select the output format: "document" (the default) or "slides".
+    pub fn set_format(&mut self, format: &str) -> Result<()> {
+        match format.to_lowercase().as_str() {
+            "document" => Ok(()),
+            "slides" => self.set_slides(&SlideOptions::default()),
+            _ => Err(anyhow!("Unknown format '{}' (expected document or slides)", format)),
+        }
+    }
+
+    /// Slide deck output: 16:9 pages split at H1/H2 headings and `---` rules, a title slide, slide numbers,
+    /// speaker notes and incremental lists. Fonts and theme colors apply as for documents. Later calls only
+    /// change the options.
+    pub fn set_slides(&mut self, options: &SlideOptions) -> Result<()> {
+        tracing::debug!("Setting slides: {:?}", options);
+        if let Some(slides) = &mut self.slides {
+            slides.merge(options);
+            return Ok(());
+        }
+        let mut slides = SlideOptions::default();
+        slides.merge(options);
+        self.slides = Some(slides);
+
+        // The 16:9 paper is already wide
+        self.set_paper("presentation-16-9")?;
+        self.set_landscape(false);
+        self.set_columns(&ColumnLayout { count: Some(1), ..ColumnLayout::default() })?;
+        self.metadata.fontsize = "20pt".to_string();
+        self.metadata.margin = Margin { x: "1.5cm".to_string(), y: "1.2cm".to_string() };
+        // Before the filters that unwrap divs, so notes and incremental blocks are still there
+        let conditionals = include_str!("assets/lua/conditionals.lua");
+        let position = self.lua_filters.iter().position(|f| f == conditionals).map_or(0, |i| i + 1);
+        self.lua_filters.insert(position, include_str!("assets/lua/slides.lua").to_string());
+        self.title_block = Some(include_str!("assets/typst/slides_title.typ").to_string());
+        self.enable_reader_extension("fenced_divs");
+        Ok(())
+    }
+
+    /// Set the paper size: a Typst paper name ("a4", "a5", "us-letter", ...), one of the aliases "letter",
+    /// "legal", "tabloid" and "executive", or custom dimensions such as "6inx9in" or "210mmx99mm".
+    pub fn set_paper(&mut self, paper: &str) -> Result<()> {
+        tracing::debug!("Setting paper size to {}", paper);
+        let paper = paper.trim().to_lowercase();
+        let name = match paper.as_str() {
+            "letter" | "legal" | "tabloid" | "executive" => format!("us-{}", paper),
+            _ => paper.clone(),
+        };
+        if TYPST_PAPERS.contains(&name.as_str()) {
+            self.metadata.papersize = name;
+            self.page_size = None;
+            return Ok(());
+        }
+        // Absolute, positive lengths only
+        let page_length =
+            |value: &str| typst_length(value).ok().filter(|l| l != "0" && !l.starts_with('-') && !l.ends_with("em"));
+        let custom = paper
+            .split_once('x')
+            .and_then(|(width, height)| Some((page_length(width)?, page_length(height)?)));
+        match custom {
+            Some(size) => {
+                self.page_size = Some(size);
+                Ok(())
+            }
+            None => Err(anyhow!(
+                "Unknown paper size '{}' (expected a4, a5, letter, legal, another Typst paper name, \
+                 or WIDTHxHEIGHT such as 6inx9in)",
+                paper
+            )),
         }
     }
 
+    /// Turn every page sideways. `::: portrait` blocks still get upright pages.
+    pub fn set_landscape(&mut self, enabled: bool) {
+        tracing::debug!("Setting landscape: {}", enabled);
+        self.landscape = enabled;
+    }
+
+    /// Put `::: landscape` and `::: portrait` blocks on pages of their own with that orientation.
+    pub fn set_orientation_blocks(&mut self) {
+        let orientation_filter = include_str!("assets/lua/orientation.lua");
+        if !self.lua_filters.iter().any(|f| f == orientation_filter) {
+            self.lua_filters.push(orientation_filter.to_string());
+        }
+        self.enable_reader_extension("fenced_divs");
+    }
+
+    /// Orientation, custom page size, title block and column rules, written to the header after the includes that may wrap
+    /// the template.
+    pub fn page_setup(&self) -> String {
+        let mut setup = String::new();
+        if self.landscape {
+            setup.push_str("#set page(flipped: true)\n");
+        }
+        // Pandoc's template is only wrapped when needed
+        if self.page_size.is_some() || self.title_block.is_some() {
+            match &self.page_size {
+                Some((width, height)) => {
+                    setup.push_str(&format!("#let quoin-page = (width: {}, height: {})\n", width, height))
+                }
+                None => setup.push_str("#let quoin-page = (:)\n"),
+            }
+            let title_block = self.title_block.as_deref().unwrap_or(include_str!("assets/typst/title_block.typ"));
+            setup.push_str(title_block);
+            setup.push_str(include_str!("assets/typst/conf.typ"));
+        }
+        if self.columns.count() > 1 {
+            setup.push_str(&format!(
+                "#let quoin-column-layout = (count: {}, gutter: {}, rule: {}, balance: {}, margin-x: {}, margin-y: {})\n",
+                self.columns.count(),
+                self.columns.gutter.as_deref().unwrap_or("0.5cm"),
+                self.columns.rule.unwrap_or(false),
+                self.columns.balance.unwrap_or(false),
+                self.metadata.margin.x,
+                self.metadata.margin.y,
+            ));
+            setup.push_str(include_str!("assets/typst/columns.typ"));
+        }
+        if let Some(slides) = &self.slides {
+            setup.push_str(&format!("#let quoin-slides = (speaker-notes: {})\n", slides.speaker_notes.unwrap_or(false)));
+            setup.push_str(include_str!("assets/typst/slides.typ"));
+        }
+        setup
+    }
+
     pub fn set_latex_font(&mut self) {
         tracing::debug!("Enabling LaTeX-style font");
         self.metadata.mainfont = Some("New Computer Modern".to_string());
     }
 
+    /// Set body, heading, monospace and math fonts, each with its fallbacks. Roles left empty keep their
+    /// current font, so calls can be layered (e.g. a config pairing, then a `--mono-font` override).
+    pub fn set_fonts(&mut self, fonts: &FontOptions) -> Result<()> {
+        let fonts = fonts.resolved()?;
+        tracing::debug!("Setting fonts: {:?}", fonts);
+        let rules: Vec<String> = fonts
+            .roles()
+            .iter()
+            .filter(|(_, families)| !families.is_empty())
+            .map(|(role, families)| {
+                let list: Vec<String> = families.iter().map(|f| typst_str(f)).collect();
+                let target = match *role {
+                    "body" => "#set text",
+                    "heading" => "#show heading: set text",
+                    "mono" => "#show raw: set text",
+                    _ => "#show math.equation: set text",
+                };
+                // A trailing comma keeps single-family lists arrays
+                format!("{}(font: ({},))", target, list.join(", "))
+            })
+            .collect();
+        // After the template's own `set text`, hence before-body
+        if !rules.is_empty() {
+            self.before_body_includes.push(rules.join("\n"));
+        }
+        self.fonts.merge(&fonts);
+        Ok(())
+    }
+
+    pub fn add_font_path(&mut self, path: impl Into<PathBuf>) -> Result<()> {
+        let path = std::path::absolute(path.into())?;
+        if !path.is_dir() {
+            return Err(anyhow!("Font path is not a directory: {}", path.display()));
+        }
+        tracing::debug!("Adding font path {}", path.display());
+        if !self.font_paths.contains(&path) {
+            self.font_paths.push(path);
+        }
+        Ok(())
+    }
+
+    /// Ignore system fonts, so output only depends on the font paths and Typst's embedded fonts.
+    pub fn set_ignore_system_fonts(&mut self, ignore: bool) {
+        self.ignore_system_fonts = ignore;
+    }
+
     pub fn set_global_defaults(&mut self) {
         let defaults = include_str!("assets/typst/defaults.typ");
         self.header_includes.push(defaults.to_string());
@@ -120,9 +1288,233 @@ impl Profile {
     }
 
     pub fn set_pretty_code(&mut self) {
-        tracing::debug!("Enabling pretty code blocks");
-        let code_style = include_str!("assets/typst/pretty_code.typ");
-        self.header_includes.push(code_style.to_string());
+        self.set_pretty_code_with(&PrettyCode::default()).expect("the default code options are valid");
+    }
+
+    /// Enable styled code blocks with line numbers, highlighted lines, title bars, a highlighting theme
+    /// and line wrapping.
+    pub fn set_pretty_code_with(&mut self, options: &PrettyCode) -> Result<()> {
+        tracing::debug!("Enabling pretty code blocks: {:?}", options);
+        if let CodeTheme::Custom(path) = &options.theme
+            && !path.is_file()
+        {
+            return Err(anyhow!(
+                "Unknown code theme '{}' (expected light, dark or the path of a .tmTheme file)",
+                path.display()
+            ));
+        }
+        let palette = match options.theme {
+            CodeTheme::Dark => DARK_CODE_PALETTE,
+            _ => LIGHT_CODE_PALETTE,
+        };
+        let mut code_style = format!(
+            "#let quoin-code-defaults = (linenos: {}, wrap: {})\n#let quoin-code-palette = {}\n",
+            options.line_numbers, options.wrap, palette
+        );
+
+        let theme_file = match &options.theme {
+            CodeTheme::Light => None,
+            CodeTheme::Dark => Some(SupportFile {
+                path: crate::cache::cache_dir("themes").join("quoin-dark.tmTheme"),
+                content: Some(include_str!("assets/themes/quoin-dark.tmTheme").to_string()),
+            }),
+            CodeTheme::Custom(path) => Some(SupportFile {
+                path: std::path::absolute(path).unwrap_or_else(|_| path.clone()),
+                content: None,
+            }),
+        };
+        if let Some(theme_file) = theme_file {
+            code_style.push_str(&format!("#set raw(theme: {})\n", typst_path(&theme_file.path)));
+            self.support_files.push(theme_file);
+        }
+
+        code_style.push_str(include_str!("assets/typst/pretty_code.typ"));
+        self.header_includes.push(code_style);
+        let code_filter = include_str!("assets/lua/code_blocks.lua");
+        self.lua_filters.push(code_filter.to_string());
+        self.enable_reader_extension("attributes");
+        Ok(())
+    }
+
+    /// Render GitHub-style alerts and `::: note` style divs as colored callout boxes.
+    pub fn set_callouts(&mut self) {
+        tracing::debug!("Enabling callout blocks");
+        let callout_style = include_str!("assets/typst/callouts.typ");
+        self.header_includes.push(callout_style.to_string());
+        let callout_filter = include_str!("assets/lua/callouts.lua");
+        self.lua_filters.push(callout_filter.to_string());
+        self.enable_reader_extension("fenced_divs");
+    }
+
+    /// Keep or drop `::: {when="audience == 'internal'"}` blocks depending on variables and metadata.
+    pub fn set_conditionals(&mut self) {
+        tracing::debug!("Enabling conditional blocks");
+        let conditional_filter = include_str!("assets/lua/conditionals.lua");
+        self.lua_filters.push(conditional_filter.to_string());
+        self.enable_reader_extension("fenced_divs");
+    }
+
+    /// Render ```mermaid, ```dot and ```plantuml code blocks to SVG figures using the locally installed tools.
+    pub fn set_diagrams(&mut self) {
+        tracing::debug!("Enabling diagram rendering");
+        let diagram_filter = include_str!("assets/lua/diagrams.lua");
+        self.lua_filters.push(diagram_filter.to_string());
+        self.diagram_cache_dir = Some(crate::cache::cache_dir("diagrams"));
+        self.enable_reader_extension("attributes");
+    }
+
+    /// Turn `{#fig:x}`, `{#tbl:x}`, `{#eq:x}`, `{#sec:x}` and `{#lst:x}` labels and `@fig:x` references
+    /// into numbered Typst references. Supplements ("Figure", "Table", ...) follow the document `lang`.
+    pub fn set_crossrefs(&mut self) {
+        tracing::debug!("Enabling cross-references");
+        let crossref_rules = include_str!("assets/typst/crossrefs.typ");
+        self.header_includes.push(crossref_rules.to_string());
+        let crossref_filter = include_str!("assets/lua/crossrefs.lua");
+        self.lua_filters.push(crossref_filter.to_string());
+        self.enable_reader_extension("attributes");
+    }
+
+    /// Enable citations and the reference list, through the `citations` extension of the GFM reader.
+    pub fn set_citations(&mut self, mode: CitationMode) {
+        tracing::debug!("Enabling citations ({:?})", mode);
+        if self.citations.is_none() {
+            let bibliography_filter = include_str!("assets/lua/bibliography.lua");
+            self.lua_filters.push(bibliography_filter.to_string());
+        }
+        self.citations = Some(mode);
+        self.enable_reader_extension("citations");
+    }
+
+    /// Add a bibliography file (BibTeX, CSL JSON, YAML, ...) and enable citations if needed.
+    pub fn add_bibliography(&mut self, path: &str) {
+        self.metadata.bibliography.push(path.to_string());
+        if self.citations.is_none() {
+            self.set_citations(CitationMode::default());
+        }
+    }
+
+    pub fn set_csl(&mut self, path: &str) {
+        self.metadata.csl = Some(path.to_string());
+    }
+
+    pub fn add_resource_path(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        tracing::debug!("Adding resource path {}", path.display());
+        self.resource_paths.push(path);
+    }
+
+    /// Warn about missing local images instead of failing the conversion with the list of them.
+    pub fn set_allow_missing_images(&mut self, allow: bool) {
+        self.allow_missing_images = allow;
+    }
+
+    /// Only read images from `dir`: images elsewhere, or missing, are replaced by their description.
+    /// For conversions of untrusted input, such as the web server's.
+    pub fn set_image_sandbox(&mut self, dir: impl Into<PathBuf>) {
+        let dir = dir.into();
+        tracing::debug!("Restricting images to {}", dir.display());
+        self.image_sandbox = Some(dir);
+    }
+
+    /// Download remote images into the local cache before compilation.
+    pub fn set_remote_images(&mut self, options: RemoteImageOptions) {
+        tracing::debug!("Enabling remote images: {:?}", options);
+        self.remote_images = Some(options);
+    }
+
+    /// Append a user Typst file to the header includes, after quoin's own.
+    pub fn add_header_include_file(&mut self, path: &Path) -> Result<()> {
+        tracing::debug!("Adding header include {}", path.display());
+        let content = read_user_file(path)?;
+        self.header_includes.push(content);
+        self.add_user_support_file(path);
+        Ok(())
+    }
+
+    /// Append a user Typst file to the after-body includes, after quoin's own.
+    pub fn add_after_body_include_file(&mut self, path: &Path) -> Result<()> {
+        tracing::debug!("Adding after-body include {}", path.display());
+        let content = read_user_file(path)?;
+        self.after_body_includes.push(content);
+        self.add_user_support_file(path);
+        Ok(())
+    }
+
+    /// Run a user Lua filter after quoin's own filters.
+    pub fn add_lua_filter_file(&mut self, path: &Path) -> Result<()> {
+        tracing::debug!("Adding Lua filter {}", path.display());
+        if !path.is_file() {
+            return Err(anyhow!("Lua filter not found: {}", path.display()));
+        }
+        self.lua_filter_files.push(std::path::absolute(path)?);
+        Ok(())
+    }
+
+    /// Keep a user file's directory inside the Typst root. The file's content is pasted into the .typ file, so
+    /// relative paths in it resolve against the .typ file's directory, not this one.
+    fn add_user_support_file(&mut self, path: &Path) {
+        if let Ok(path) = std::path::absolute(path) {
+            self.support_files.push(SupportFile { path, content: None });
+        }
+    }
+
+    /// Pull Markdown fragments and code snippets from other files (`!include`, `include=`).
+    pub fn set_includes(&mut self, enabled: bool) {
+        self.expand_includes = enabled;
+    }
+
+    /// Substitute `{{name}}` placeholders in the text, outside code; with `strict`, undefined names are an error.
+    pub fn set_variable_substitution(&mut self, strict: bool) {
+        self.substitute_variables = true;
+        self.strict_variables = strict;
+    }
+
+    pub fn enable_reader_extension(&mut self, extension: &str) {
+        if !self.reader_extensions.iter().any(|e| e == extension) {
+            self.reader_extensions.push(extension.to_string());
+        }
+    }
+
+    /// Set theme colors and the logo. Unset fields keep their current value.
+    pub fn set_theme(&mut self, theme: &Theme) -> Result<()> {
+        tracing::debug!("Setting theme: {:?}", theme);
+        let mut merged = self.theme.clone();
+        merged.merge(theme);
+        if let Some(logo) = &theme.logo {
+            if !logo.is_file() {
+                return Err(anyhow!("Logo not found: {}", logo.display()));
+            }
+            let logo = std::path::absolute(logo)?;
+            self.support_files.push(SupportFile { path: logo.clone(), content: None });
+            merged.logo = Some(logo);
+        }
+        // Fails early on invalid colors, rather than at conversion
+        merged.prelude()?;
+        // Before-body, so that heading styles set later can still override the heading color
+        let rules = theme.rules()?;
+        if !rules.is_empty() {
+            self.before_body_includes.push(rules.join("\n"));
+        }
+        self.theme = merged;
+        Ok(())
+    }
+
+    /// Set heading numbering, appendix numbering, per-level sizes, weights and colors, spacing, page breaks
+    /// and keep-with-next. Unset fields keep their current value.
+    pub fn set_heading_style(&mut self, style: &HeadingStyle) -> Result<()> {
+        tracing::debug!("Setting heading style: {:?}", style);
+        let rules = style.rules()?;
+        // After the template's own `set heading`, hence before-body
+        if !rules.is_empty() {
+            self.before_body_includes.push(rules.join("\n"));
+        }
+        if style.appendix.is_some() && self.headings.appendix.is_none() {
+            let appendix_filter = include_str!("assets/lua/appendix.lua");
+            self.lua_filters.push(appendix_filter.to_string());
+            self.enable_reader_extension("attributes");
+        }
+        self.headings.merge(style);
+        Ok(())
     }
 
     pub fn set_section_numbering(&mut self, enabled: bool) {
@@ -135,23 +1527,89 @@ impl Profile {
     }
 
     pub fn set_outline(&mut self) {
-        tracing::debug!("Enabling document outline (TOC)");
-        let outline_style = include_str!("assets/typst/outline.typ");
-        self.after_body_includes.push(outline_style.to_string());
+        self.set_outline_with(&OutlineOptions::default()).expect("the default outline options are valid");
+    }
+
+    /// Enable the document outline (TOC) with custom placement, depth, title, indentation and extra lists.
+    pub fn set_outline_with(&mut self, options: &OutlineOptions) -> Result<()> {
+        tracing::debug!("Enabling document outline (TOC): {:?}", options);
+        let indent = match &options.indent {
+            Some(indent) => typst_length(indent)?,
+            None => "auto".to_string(),
+        };
+        let outline_helpers = include_str!("assets/typst/outline.typ");
+        if !self.header_includes.iter().any(|i| i == outline_helpers) {
+            self.header_includes.push(outline_helpers.to_string());
+        }
+        // A second call (a preset, then the command line) replaces the outline rather than adding another
+        if let Some(previous) = self.outline_include.take() {
+            self.before_body_includes.retain(|include| *include != previous);
+            self.after_body_includes.retain(|include| *include != previous);
+        }
+
+        let title = match &options.title {
+            Some(title) => typst_str(title),
+            None => "quoin-labels.contents".to_string(),
+        };
+        let depth = match options.depth {
+            Some(depth) => depth.to_string(),
+            None => "none".to_string(),
+        };
+
+        let mut outline = String::new();
+        if options.position == OutlinePosition::End {
+            outline.push_str("#pagebreak(weak: true)\n");
+        }
+        outline.push_str(&format!("#outline(title: {}, depth: {}, indent: {})\n", title, depth, indent));
+        if options.list_of_figures {
+            outline.push_str("#quoin-list-of(image, \"figure\")\n");
+        }
+        if options.list_of_tables {
+            outline.push_str("#quoin-list-of(table, \"table\")\n");
+        }
+        if options.list_of_listings {
+            outline.push_str("#quoin-list-of(raw, \"listing\")\n");
+        }
+
+        match options.position {
+            OutlinePosition::Start => {
+                outline.push_str("#pagebreak(weak: true)\n");
+                self.before_body_includes.push(outline.clone());
+            }
+            OutlinePosition::End => self.after_body_includes.push(outline.clone()),
+        }
+        self.outline_include = Some(outline);
+        Ok(())
     }
 
     pub fn override_variable(&mut self, key: &str, value: &str) {
         tracing::debug!("Overriding variable {} = {}", key, value);
+        self.variables.insert(key.to_string(), value.to_string());
         // Attempt to set structured fields first
         match key {
             "fontsize" => self.metadata.fontsize = value.to_string(),
             "lang" => self.metadata.lang = value.to_string(),
-            "papersize" => self.metadata.papersize = value.to_string(),
+            "papersize" => {
+                if let Err(e) = self.set_paper(value) {
+                    tracing::warn!("{}; keeping the current paper size", e);
+                }
+            }
             "margin.x" => self.metadata.margin.x = value.to_string(),
             "margin.y" => self.metadata.margin.y = value.to_string(),
-            "columns" => if let Ok(n) = value.parse() { self.metadata.columns = n },
+            "columns" => {
+                let layout = ColumnLayout { count: value.parse().ok(), ..ColumnLayout::default() };
+                if let Err(e) = self.set_columns(&layout) {
+                    tracing::warn!("{}", e);
+                }
+            }
             "mainfont" => self.metadata.mainfont = Some(value.to_string()),
             "section-numbering" | "sectionnumbering" => self.metadata.section_numbering = Some(value.to_string()),
+            "class" => match DocumentClass::parse(value) {
+                Ok(class) => self.set_class(class),
+                Err(e) => tracing::warn!("{}", e),
+            },
+            "bibliography" => self.add_bibliography(value),
+            "csl" => self.set_csl(value),
             _ => {
                 // Support dotted keys for nesting in extra
                 let parts: Vec<&str> = key.split('.').collect();
@@ -173,3 +1631,104 @@ impl Profile {
         }
     }
 }
+
+/// Paper names understood by Typst's `page(paper: ..)`.
+const TYPST_PAPERS: &[&str] = &[
+    "a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7", "a8", "a9", "a10", "a11", "iso-b1", "iso-b2", "iso-b3",
+    "iso-b4", "iso-b5", "iso-b6", "iso-b7", "iso-b8", "iso-c3", "iso-c4", "iso-c5", "iso-c6", "iso-c7", "iso-c8",
+    "jis-b0", "jis-b1", "jis-b2", "jis-b3", "jis-b4", "jis-b5", "jis-b6", "jis-b7", "jis-b8", "jis-b9", "jis-b10",
+    "jis-b11", "us-letter", "us-legal", "us-tabloid", "us-executive", "us-foolscap-folio", "us-statement",
+    "us-ledger", "us-oficio", "us-gov-letter", "us-gov-legal", "us-business-card", "us-digest", "us-trade",
+    "ansi-a", "ansi-b", "ansi-c", "ansi-d", "ansi-e", "presentation-16-9", "presentation-4-3",
+];
+
+const LIGHT_CODE_PALETTE: &str = "(inline: quoin-theme.code-inline, background: quoin-theme.code-background, stroke: luma(220), foreground: none, \
+    line-number: luma(150), highlight: rgb(\"#fff5b1\"), title-background: luma(235), title-foreground: luma(60))";
+
+const DARK_CODE_PALETTE: &str = "(inline: quoin-theme.code-inline, background: rgb(\"#282c34\"), stroke: rgb(\"#3e4451\"), \
+    foreground: rgb(\"#abb2bf\"), line-number: rgb(\"#636d83\"), highlight: rgb(\"#3e4451\"), \
+    title-background: rgb(\"#21252b\"), title-foreground: rgb(\"#d7dae0\"))";
+
+fn read_user_file(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read include {}: {}", path.display(), e))
+}
+
+/// Checks that `value` is a Typst length (e.g. "1.2em", "4pt", "0.5cm") so it can be emitted unquoted.
+pub(crate) fn typst_length(value: &str) -> Result<String> {
+    let value = value.trim();
+    let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = &value[number.len()..];
+    let valid = match number.parse::<f64>() {
+        Ok(n) => matches!(unit, "pt" | "mm" | "cm" | "in" | "em") || (unit.is_empty() && n == 0.0),
+        Err(_) => false,
+    };
+    if !valid {
+        return Err(anyhow!("Invalid length '{}' (expected e.g. 1.2em, 4pt, 0.5cm)", value));
+    }
+    Ok(value.to_string())
+}
+
+/// Typst font weight: a named weight or a number from 100 to 900.
+pub(crate) fn typst_weight(value: &str) -> Result<String> {
+    const NAMES: &[&str] =
+        &["thin", "extralight", "light", "regular", "medium", "semibold", "bold", "extrabold", "black"];
+    let value = value.trim();
+    if NAMES.contains(&value) {
+        return Ok(typst_str(value));
+    }
+    match value.parse::<u16>() {
+        Ok(weight) if (100..=900).contains(&weight) => Ok(weight.to_string()),
+        _ => Err(anyhow!("Invalid font weight '{}' (expected e.g. bold, semibold or 600)", value)),
+    }
+}
+
+/// Typst color from "#rgb", "#rrggbb", "#rrggbbaa" or one of Typst's predefined color names.
+pub(crate) fn typst_color(value: &str) -> Result<String> {
+    const NAMES: &[&str] = &[
+        "black", "gray", "silver", "white", "navy", "blue", "aqua", "teal", "eastern", "purple", "fuchsia",
+        "maroon", "red", "orange", "yellow", "olive", "green", "lime",
+    ];
+    let value = value.trim();
+    if NAMES.contains(&value) {
+        return Ok(value.to_string());
+    }
+    match value.strip_prefix('#') {
+        Some(hex) if matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(format!("rgb(\"{}\")", value))
+        }
+        _ => Err(anyhow!("Invalid color '{}' (expected e.g. #1f4e79 or navy)", value)),
+    }
+}
+
+/// Quote a path as a Typst string literal, with forward slashes.
+pub(crate) fn typst_path(path: &std::path::Path) -> String {
+    typst_str(&path.to_string_lossy().replace('\\', "/"))
+}
+
+/// A front matter value as a Typst string, an array of strings, or `none`.
+fn typst_field(value: Option<&Value>) -> String {
+    let text = |value: &Value| match value {
+        Value::String(s) => Some(typst_str(s.trim_end()).replace('\n', "\\n")),
+        Value::Number(n) => Some(typst_str(&n.to_string())),
+        Value::Bool(b) => Some(typst_str(&b.to_string())),
+        _ => None,
+    };
+    match value {
+        Some(Value::Sequence(items)) => {
+            let items: Vec<String> = items.iter().filter_map(text).collect();
+            if items.is_empty() {
+                "()".to_string()
+            } else {
+                format!("({},)", items.join(", "))
+            }
+        }
+        Some(value) => text(value).unwrap_or_else(|| "none".to_string()),
+        None => "none".to_string(),
+    }
+}
+
+/// Quote a string as a Typst string literal.
+pub(crate) fn typst_str(value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
@@ -0,0 +1,9 @@
+/// Marks `\{{` escapes before Pandoc parses the document, since its Markdown reader drops the backslash. The
+/// variables filter turns the mark back into `{{` in text, and into `\{{` in code and raw content.
+pub const ESCAPE_MARKER: &str = "{\u{E000}{";
+
+/// Prepares a document for the `{{name}}` substitution done by the variables filter: `\{{name}}` escapes
+/// are marked so they survive parsing.
+pub fn mark_escapes(markdown: &str) -> String {
+    markdown.replace("\\{{", ESCAPE_MARKER)
+}
@@ -0,0 +1,98 @@
+use mlua::{Lua, Table, Value};
+
+/// A minimal stand-in for Pandoc's Lua API: enough for the parts of quoin's filters that only work on
+/// strings, lists, metadata, `Str`/`Space` inlines and POSIX paths.
+const PANDOC_STUB: &str = r#"
+local List = { __name = "List" }
+List.__index = List
+function List:insert(value) table.insert(self, value) end
+function List:extend(values) for _, value in ipairs(values) do table.insert(self, value) end return self end
+
+pandoc = {}
+pandoc.List = function(values) return setmetatable(values or {}, List) end
+pandoc.Str = function(text) return { t = "Str", text = text } end
+pandoc.Space = function() return { t = "Space" } end
+pandoc.Code = function(text) return { t = "Code", text = text } end
+pandoc.Inlines = function(value)
+  if type(value) ~= "string" then
+    return pandoc.List(value)
+  end
+  local inlines = pandoc.List()
+  for spaces, word in value:gmatch("( *)([^ ]*)") do
+    if spaces ~= "" then inlines:insert(pandoc.Space()) end
+    if word ~= "" then inlines:insert(pandoc.Str(word)) end
+  end
+  return inlines
+end
+pandoc.utils = {
+  type = function(value)
+    if type(value) == "table" then
+      local mt = getmetatable(value)
+      return mt and mt.__name or "table"
+    end
+    return type(value)
+  end,
+  stringify = function(value)
+    if type(value) ~= "table" then return tostring(value) end
+    if value.t == "Str" or value.t == "Code" then return value.text end
+    if value.t == "Space" then return " " end
+    local parts = {}
+    for _, item in ipairs(value) do table.insert(parts, pandoc.utils.stringify(item)) end
+    return table.concat(parts)
+  end,
+}
+-- POSIX paths only
+pandoc.path = {
+  is_absolute = function(path) return path:sub(1, 1) == "/" end,
+  split = function(path)
+    local parts = path:sub(1, 1) == "/" and { "/" } or {}
+    for part in path:gmatch("[^/]+") do table.insert(parts, part) end
+    return parts
+  end,
+  join = function(parts)
+    local path = ""
+    for _, part in ipairs(parts) do
+      if part == "/" or path == "" or path:sub(-1) == "/" then path = path .. part else path = path .. "/" .. part end
+    end
+    return path
+  end,
+  make_relative = function(path, root)
+    local from, to = pandoc.path.split(root), pandoc.path.split(path)
+    local common = 0
+    while from[common + 1] and from[common + 1] == to[common + 1] do common = common + 1 end
+    local parts = {}
+    for _ = common + 1, #from do table.insert(parts, "..") end
+    for i = common + 1, #to do table.insert(parts, to[i]) end
+    return #parts > 0 and table.concat(parts, "/") or "."
+  end,
+}
+PANDOC_STATE = { resource_path = {} }
+
+warnings = {}
+io.stderr = { write = function(_, message) table.insert(warnings, message) end }
+"#;
+
+/// Loads a Lua filter with the Pandoc stub and the given environment variables. The list of filters the file
+/// returns is stored in the `filters` global.
+pub fn load_filter(source: &str, env: &[(&str, &str)]) -> mlua::Result<Lua> {
+    let lua = Lua::new();
+    load_into(&lua, source, env)?;
+    Ok(lua)
+}
+
+fn load_into(lua: &Lua, source: &str, env: &[(&str, &str)]) -> mlua::Result<()> {
+    lua.load(PANDOC_STUB).exec()?;
+    let vars = lua.create_table()?;
+    for (key, value) in env {
+        vars.set(*key, *value)?;
+    }
+    let getenv = lua.create_function(move |lua, key: String| {
+        let vars: Table = lua.named_registry_value("env")?;
+        vars.get::<_, Option<String>>(key)
+    })?;
+    lua.set_named_registry_value("env", vars)?;
+    let os: Table = lua.globals().get("os")?;
+    os.set("getenv", getenv)?;
+    let filters: Value = lua.load(source).eval()?;
+    lua.globals().set("filters", filters)
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use mlua::{Function, Lua, Table, Value};
+
+const FILTER: &str = include_str!("../src/assets/lua/conditionals.lua");
+
+/// Runs the filter on a div built by the Lua expression `div`, after reading `meta`. What the filter returns is
+/// stored in the `result` global; the warnings are returned, or the error raised in strict mode.
+fn filter_div(div: &str, meta: &str, env: &[(&str, &str)]) -> mlua::Result<(Lua, Vec<String>)> {
+    let lua = common::load_filter(FILTER, env)?;
+    let warnings = {
+        let filters: Table = lua.globals().get("filters")?;
+        let meta: Table = lua.load(meta).eval()?;
+        filters.get::<_, Table>(1)?.get::<_, Function>("Meta")?.call::<_, ()>(meta)?;
+        let div: Table = lua.load(div).eval()?;
+        let result: Value = filters.get::<_, Table>(2)?.get::<_, Function>("Div")?.call(div)?;
+        lua.globals().set("result", result)?;
+        lua.globals().get::<_, Vec<String>>("warnings")?
+    };
+    Ok((lua, warnings))
+}
+
+/// Evaluates a `when` condition the way the conditionals filter does for a `::: {when=...}` div. Returns
+/// whether the block is kept and the warnings, or the error raised in strict mode.
+fn when(condition: &str, meta: &str, env: &[(&str, &str)]) -> mlua::Result<(bool, Vec<String>)> {
+    let div = format!(
+        "{{ identifier = \"\", classes = pandoc.List(), attributes = {{ when = {:?} }}, content = \"body\" }}",
+        condition
+    );
+    let (lua, warnings) = filter_div(&div, meta, env)?;
+    Ok((matches!(lua.globals().get("result")?, Value::String(_)), warnings))
+}
+
+fn kept(condition: &str, env: &[(&str, &str)]) -> bool {
+    let meta = r#"{ author = { name = "Ada" }, tags = pandoc.List({ "print", "web" }), draft = false }"#;
+    let (kept, warnings) = when(condition, meta, env).unwrap();
+    assert!(warnings.is_empty(), "{}: {:?}", condition, warnings);
+    kept
+}
+
+#[test]
+fn test_operators() {
+    let env = [("QUOIN_VARIABLES", "audience=internal\nversion=2\nformats=pdf, html\nquiet=off")];
+    assert!(kept("audience == 'internal'", &env));
+    assert!(!kept("audience != 'internal'", &env));
+    assert!(kept("version == 2", &env));
+    assert!(kept("audience in ['internal', 'staff']", &env));
+    assert!(kept("audience not in ['public']", &env));
+    assert!(kept("'html' in formats", &env));
+    assert!(kept("'web' in tags", &env));
+    assert!(!kept("'epub' in tags", &env));
+    assert!(kept("author.name == 'Ada'", &env));
+    assert!(kept("audience", &env));
+    assert!(!kept("quiet", &env));
+    assert!(!kept("draft", &env));
+    assert!(kept("!draft && audience == 'internal'", &env));
+    assert!(kept("draft || version == 2", &env));
+}
+
+#[test]
+fn test_precedence() {
+    let env = [("QUOIN_VARIABLES", "a=yes\nb=no\nc=no")];
+    // `and` binds tighter than `or`, and `not` tighter than both
+    assert!(kept("a or b and c", &env));
+    assert!(!kept("(a or b) and c", &env));
+    assert!(kept("not b and a", &env));
+    assert!(!kept("not (b or a)", &env));
+    assert!(kept("not not a", &env));
+    assert!(!kept("true and false or false", &env));
+}
+
+#[test]
+fn test_quoting() {
+    let env = [("QUOIN_VARIABLES", "title=it's a guide\nname=two words")];
+    assert!(kept(r#"title == "it's a guide""#, &env));
+    assert!(kept("name == 'two words'", &env));
+    assert!(kept(r#"name in ["one", 'two words']"#, &env));
+    assert!(!kept("name == 'two'", &env));
+}
+
+#[test]
+fn test_unset_variables() {
+    let (kept, warnings) = when("edition == 'pro'", "{}", &[]).unwrap();
+    assert!(!kept);
+    assert_eq!(
+        warnings,
+        vec!["[quoin] Warning: unknown variable(s) edition in when=\"edition == 'pro'\"; they are treated as unset\n"]
+    );
+    assert!(when("edition != 'pro'", "{}", &[]).unwrap().0);
+    assert!(when("not edition", "{}", &[]).unwrap().0);
+
+    let strict = [("QUOIN_STRICT_VARIABLES", "1")];
+    let error = when("edition", "{}", &strict).unwrap_err().to_string();
+    assert!(error.contains("[quoin] unknown variable(s) edition"), "{}", error);
+}
+
+#[test]
+fn test_invalid_conditions() {
+    for condition in ["audience ==", "'unterminated", "audience = 'x'", "(a or b", "a b"] {
+        let (kept, warnings) = when(condition, "{}", &[]).unwrap();
+        assert!(kept, "{}", condition);
+        assert!(warnings[0].contains("invalid condition") && warnings[0].ends_with("; keeping the block\n"));
+    }
+    let error = when("a ==", "{}", &[("QUOIN_STRICT_VARIABLES", "1")]).unwrap_err().to_string();
+    assert!(error.contains("[quoin] invalid condition when=\"a ==\": unexpected end of expression"), "{}", error);
+}
+
+#[test]
+fn test_kept_blocks_keep_their_attributes() {
+    let div = r#"{ identifier = "tbl:x", classes = pandoc.List({ "warning" }), attributes = { when = "true" },
+                   content = "body" }"#;
+    let (lua, warnings) = filter_div(div, "{}", &[]).unwrap();
+    assert!(warnings.is_empty());
+    let div: Table = lua.globals().get("result").unwrap();
+    assert_eq!(div.get::<_, String>("identifier").unwrap(), "tbl:x");
+    assert_eq!(div.get::<_, Vec<String>>("classes").unwrap(), ["warning"]);
+    assert!(div.get::<_, Table>("attributes").unwrap().get::<_, Option<String>>("when").unwrap().is_none());
+}
@@ -0,0 +1,69 @@
+use quoin::config::Config;
+use quoin::styles::Profile;
+use std::fs;
+
+#[test]
+fn test_config_presets() {
+    let root = tempfile::tempdir().unwrap();
+    let brand = root.path().join("brand");
+    fs::create_dir_all(&brand).unwrap();
+    fs::write(brand.join("header.typ"), "#let brand = \"header\"").unwrap();
+    fs::write(brand.join("handout.typ"), "#let brand = \"handout\"").unwrap();
+    fs::write(brand.join("glossary.lua"), "return {}").unwrap();
+    fs::write(
+        root.path().join("quoin.toml"),
+        r##"
+include_header = ["brand/header.typ"]
+lua_filters = ["brand/glossary.lua"]
+font_paths = ["brand"]
+ignore_system_fonts = true
+
+[variables]
+version = "1.4.0"
+draft = true
+
+[typography]
+leading = "0.6em"
+
+[typography.standard]
+justify = false
+
+[typography.dense]
+leading = "0.4em"
+
+[theme]
+primary = "#1f4e79"
+
+[labels.fr]
+figure = "Fig."
+
+[presets.handout]
+include_header = ["brand/handout.typ"]
+"##,
+    )
+    .unwrap();
+
+    let config = Config::discover(None, root.path()).unwrap().unwrap();
+    let mut profile = Profile::new();
+    profile.set_callouts();
+    config.apply(&mut profile, Some("handout")).unwrap();
+
+    // Built-in includes first, then the config file, then the preset
+    let includes = &profile.header_includes;
+    assert_eq!(includes[includes.len() - 2..], ["#let brand = \"header\"", "#let brand = \"handout\""]);
+    assert_eq!(profile.lua_filter_files, vec![brand.join("glossary.lua")]);
+    assert_eq!(profile.font_paths, vec![brand.clone()]);
+    assert!(profile.ignore_system_fonts);
+    assert_eq!(profile.typography.leading.as_deref(), Some("0.6em"));
+    assert_eq!(profile.typography.justify, Some(false));
+    assert_eq!(profile.theme.primary.as_deref(), Some("#1f4e79"));
+    assert_eq!(profile.theme.table_header.as_deref(), Some("#e4e4e4"));
+    assert_eq!(profile.variables["version"], "1.4.0");
+    assert_eq!(profile.variables["draft"], "true");
+    assert_eq!(profile.labels["fr"]["figure"], "Fig.");
+    assert!(!profile.labels.contains_key("en"));
+
+    let error = config.apply(&mut Profile::new(), Some("poster")).unwrap_err();
+    assert_eq!(error.to_string(), "Unknown preset 'poster' (available: handout)");
+    assert!(Config::discover(None, &brand).unwrap().is_none());
+}
@@ -0,0 +1,62 @@
+use quoin::fonts::{FontFamily, cjk_families, cjk_font_warning, missing_font_warnings, parse_typst_fonts};
+use quoin::styles::{FontOptions, Profile};
+
+#[test]
+fn test_font_pairings_and_validation() {
+    let mut profile = Profile::new();
+    profile
+        .set_fonts(&FontOptions { pairing: Some("technical".to_string()), ..FontOptions::default() })
+        .unwrap();
+    profile.set_fonts(&FontOptions { mono: vec!["Iosevka".to_string()], ..FontOptions::default() }).unwrap();
+
+    assert!(profile.before_body_includes[0].contains("#set text(font: (\"IBM Plex Sans\", \"Source Sans 3\", \"DejaVu Sans\",))"));
+    assert_eq!(profile.before_body_includes[1], "#show raw: set text(font: (\"Iosevka\",))");
+    assert_eq!(profile.fonts.mono, vec!["Iosevka"]);
+    assert_eq!(profile.fonts.math, vec!["STIX Two Math", "New Computer Modern Math"]);
+
+    let unknown = FontOptions { pairing: Some("poster".to_string()), ..FontOptions::default() };
+    assert!(profile.set_fonts(&unknown).is_err());
+
+    let available = parse_typst_fonts(
+        "DejaVu Sans\n- Style: Normal, Weight: 400, Stretch: 100%\n- Style: Normal, Weight: 700, Stretch: 100%\n\
+         New Computer Modern Math\n- Style: Normal, Weight: 400, Stretch: 100%\n",
+    );
+    assert_eq!(available.len(), 2);
+    assert_eq!(available[0].variants.len(), 2);
+    assert_eq!(available[0].variants[1].to_string(), "normal 700");
+    assert_eq!(available[1].source, "typst");
+
+    let warnings = missing_font_warnings(&profile.fonts, &available);
+    assert_eq!(
+        warnings,
+        vec![
+            "body font(s) 'IBM Plex Sans', 'Source Sans 3' not available to Typst; using 'DejaVu Sans'",
+            "heading font(s) 'IBM Plex Sans', 'Source Sans 3' not available to Typst; using 'DejaVu Sans'",
+            "none of the mono fonts (Iosevka) are available to Typst; it will substitute its default font",
+            "math font(s) 'STIX Two Math' not available to Typst; using 'New Computer Modern Math'",
+        ]
+    );
+}
+
+#[test]
+fn test_cjk_fonts() {
+    assert_eq!(cjk_families("zh").unwrap().1[0], "Noto Sans CJK SC");
+    assert_eq!(cjk_families("zh-Hant-TW").unwrap().0[0], "Noto Serif CJK TC");
+    assert_eq!(cjk_families("ja_JP").unwrap().0[0], "Noto Serif CJK JP");
+    assert!(cjk_families("fr").is_none());
+
+    let mut available = parse_typst_fonts("Libertinus Serif\n- Style: Normal, Weight: 400, Stretch: 100%\n");
+    let warning = cjk_font_warning("ko", &available).unwrap();
+    assert!(warning.starts_with("no font with Korean Hangul glyphs is available to Typst for lang 'ko'"));
+    assert!(warning.contains("'Noto Sans CJK KR'"));
+    assert!(cjk_font_warning("en", &available).is_none());
+
+    available.push(FontFamily {
+        name: "Noto Sans CJK JP".to_string(),
+        variants: Vec::new(),
+        scripts: vec!["Latin".to_string(), "CJK".to_string(), "Japanese kana".to_string()],
+        source: "system".to_string(),
+    });
+    assert!(cjk_font_warning("ja", &available).is_none());
+    assert!(cjk_font_warning("zh", &available).is_none());
+}
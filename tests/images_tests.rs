@@ -0,0 +1,57 @@
+mod common;
+
+use mlua::{Function, Table};
+use quoin::images::{find_images, normalize, relative_path};
+use std::path::Path;
+
+#[test]
+fn test_find_images() {
+    let markdown = r#"# Images
+
+![Inline](img/a.png "Title") and ![Spaced](<my image.png>)
+`![not an image](code.png)`
+
+```markdown
+![Fenced](fenced.png)
+```
+
+![Reference][logo]
+<img alt="html" src="html.png" width="10">
+[logo]: img/logo.svg
+[link]: not-an-image.html
+"#;
+    let urls: Vec<String> = find_images(markdown).into_iter().map(|image| image.url).collect();
+    assert_eq!(urls, vec!["img/a.png", "my image.png", "html.png", "img/logo.svg"]);
+}
+
+#[test]
+fn test_relative_paths() {
+    assert_eq!(normalize(Path::new("/docs/./img/../shot.png")), Path::new("/docs/shot.png"));
+    assert_eq!(relative_path(Path::new("/docs/img/shot.png"), Path::new("/docs")), Path::new("img/shot.png"));
+    assert_eq!(relative_path(Path::new("/shared.png"), Path::new("/docs/out")), Path::new("../../shared.png"));
+}
+
+#[test]
+fn test_missing_images_are_listed() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("found.png"), b"\x89PNG").unwrap();
+    let dir_path = dir.path().to_str().unwrap();
+    let run = |env: &[(&str, &str)]| -> mlua::Result<Vec<String>> {
+        let lua = common::load_filter(include_str!("../src/assets/lua/images.lua"), env)?;
+        lua.load(format!("PANDOC_STATE.resource_path = {{ {:?} }}", dir_path)).exec()?;
+        let filters: Table = lua.globals().get("filters")?;
+        let image: Function = filters.get::<_, Table>(1)?.get("Image")?;
+        for src in ["found.png", "a.png", "found.png", "b.png", "a.png"] {
+            let el = lua.create_table()?;
+            el.set("src", src)?;
+            image.call::<_, mlua::Value>(el)?;
+        }
+        filters.get::<_, Table>(2)?.get::<_, Function>("Pandoc")?.call::<_, ()>(())?;
+        lua.globals().get("warnings")
+    };
+
+    let error = run(&[("QUOIN_TYPST_DIR", dir_path)]).unwrap_err().to_string();
+    assert!(error.contains("[quoin] 2 image(s) not found in the resource path:\n  - a.png\n  - b.png"), "{}", error);
+    let warnings = run(&[("QUOIN_ALLOW_MISSING_IMAGES", "1")]).unwrap();
+    assert_eq!(warnings, ["[quoin] Warning: leaving 2 missing image(s) as written:\n  - a.png\n  - b.png\n"]);
+}
@@ -0,0 +1,40 @@
+use quoin::includes::expand_includes;
+use std::fs;
+
+#[test]
+fn test_expand_includes() {
+    let root = tempfile::tempdir().unwrap();
+    let chapters = root.path().join("chapters");
+    fs::create_dir_all(&chapters).unwrap();
+    fs::write(root.path().join("lib.rs"), "fn one() {}\nfn two() {}\nfn three() {}\n").unwrap();
+    fs::write(
+        chapters.join("two.md"),
+        "---\ntitle: ignored\n---\n## Two\n\n![Shot](img/shot.png)\n\n```{.rust include=\"../lib.rs\" lines=\"2-3\"}\n```\n",
+    )
+    .unwrap();
+    let main = root.path().join("main.md");
+    fs::write(&main, "# One\n\n!include chapters/two.md\n\n```\n!include not-expanded.md\n```\n").unwrap();
+
+    let expanded = expand_includes(&fs::read_to_string(&main).unwrap(), root.path(), Some(&main)).unwrap();
+    let shot = chapters.join("img/shot.png").to_string_lossy().replace('\\', "/");
+    assert_eq!(
+        expanded.markdown,
+        format!(
+            "# One\n\n## Two\n\n![Shot]({})\n\n```{{.rust include=\"../lib.rs\" lines=\"2-3\" startFrom=2}}\nfn two() {{}}\nfn three() {{}}\n```\n\n```\n!include not-expanded.md\n```\n",
+            shot
+        )
+    );
+    assert_eq!(expanded.files, vec![chapters.join("two.md"), root.path().join("lib.rs")]);
+
+    // A file including itself, indirectly
+    fs::write(chapters.join("two.md"), "!include ../main.md\n").unwrap();
+    let error = expand_includes(&fs::read_to_string(&main).unwrap(), root.path(), Some(&main)).unwrap_err();
+    let two = chapters.join("two.md");
+    let cycle = format!("Include cycle detected: {} -> {} -> {}", main.display(), two.display(), main.display());
+    assert_eq!(error.to_string(), cycle);
+
+    // A line range past the end of the file
+    fs::write(&main, "```{include=\"lib.rs\" lines=\"3-5\"}\n```\n").unwrap();
+    let error = expand_includes(&fs::read_to_string(&main).unwrap(), root.path(), Some(&main)).unwrap_err();
+    assert!(error.to_string().starts_with("Line range '3-5' is past the end of included file"), "{}", error);
+}
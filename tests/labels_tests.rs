@@ -0,0 +1,49 @@
+use quoin::labels;
+use quoin::styles::Profile;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_label_catalog() {
+    // Every catalog language has every label
+    for lang in ["en", "fr", "de", "es", "it", "pt", "ar", "he", "zh", "ja", "ko"] {
+        assert_eq!(labels::for_lang(lang).len(), labels::KEYS.len(), "{}", lang);
+    }
+    assert_eq!(labels::for_lang("fr-CA")["table"], "Tableau");
+    // Without a catalog, Typst's own translations are kept for the outline title and supplements
+    let dutch = labels::for_lang("nl");
+    assert_eq!(dutch["note"], "Note");
+    assert!(!dutch.contains_key("contents"));
+
+    let prelude = labels::prelude("nl", &BTreeMap::new());
+    assert!(prelude.contains("contents: auto,"));
+    assert!(!prelude.contains("set figure(supplement"));
+    assert!(prelude.contains("#let quoin-rtl = false"));
+}
+
+#[test]
+fn test_labels_and_direction() {
+    let mut profile = Profile::new();
+    profile.override_variable("lang", "ar");
+    let overrides = BTreeMap::from([("figure".to_string(), "رسم".to_string())]);
+    profile.set_labels("ar", &overrides).unwrap();
+    profile.set_labels("fr", &BTreeMap::from([("figure".to_string(), "Fig.".to_string())])).unwrap();
+    let prelude = profile.labels_prelude();
+    assert!(prelude.contains("  page-of: \"صفحة {page} من {total}\",\n"));
+    assert!(prelude.contains("#show figure.where(kind: image): set figure(supplement: \"رسم\")"));
+    assert!(prelude.contains("#let quoin-rtl = true\n"));
+    assert!(prelude.ends_with("#set text(dir: rtl)\n#set page(binding: right)\n"));
+    assert!(labels::is_rtl("he-IL"));
+
+    // Overrides only apply to their language, the full tag's on top of the primary subtag's
+    profile.override_variable("lang", "fr-CA");
+    assert!(profile.labels_prelude().contains("  figure: \"Fig.\",\n"));
+    profile.set_labels("fr-CA", &BTreeMap::from([("figure".to_string(), "Figure".to_string())])).unwrap();
+    assert!(profile.labels_prelude().contains("  figure: \"Figure\",\n"));
+    profile.override_variable("lang", "en");
+    assert!(profile.labels_prelude().contains("  figure: \"Figure\",\n"));
+    assert!(!profile.labels_prelude().contains("رسم"));
+
+    let unknown = BTreeMap::from([("chapter".to_string(), "Kapitel".to_string())]);
+    assert!(profile.set_labels("de", &unknown).is_err());
+    assert!(profile.set_labels("de/../x", &BTreeMap::new()).is_err());
+}
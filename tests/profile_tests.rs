@@ -0,0 +1,354 @@
+use quoin::frontmatter;
+use quoin::styles::{
+    CitationMode, CodeTheme, ColumnLayout, DocumentClass, FontOptions, HeadingStyle, OutlineOptions, OutlinePosition,
+    PrettyCode, Profile, SlideOptions, Theme, Typography,
+};
+
+#[test]
+fn test_outline_placement() {
+    let mut profile = Profile::new();
+    profile.set_outline();
+    assert!(profile.before_body_includes.is_empty());
+    assert!(profile.after_body_includes[0].contains("#outline(title: quoin-labels.contents, depth: none, indent: auto)"));
+
+    let mut profile = Profile::new();
+    profile.set_outline_with(&OutlineOptions {
+        position: OutlinePosition::Start,
+        depth: Some(2),
+        title: Some("Sommaire \"court\"".to_string()),
+        list_of_tables: true,
+        ..OutlineOptions::default()
+    })
+    .unwrap();
+    assert!(profile.after_body_includes.is_empty());
+    let outline = &profile.before_body_includes[0];
+    assert!(outline.contains("#outline(title: \"Sommaire \\\"court\\\"\", depth: 2, indent: auto)"));
+    assert!(outline.contains("#quoin-list-of(table, \"table\")"));
+    assert!(!outline.contains("#quoin-list-of(image"));
+
+    // Setting the outline again replaces it
+    profile.set_outline_with(&OutlineOptions { depth: Some(3), ..OutlineOptions::default() }).unwrap();
+    assert!(profile.before_body_includes.is_empty());
+    assert_eq!(profile.after_body_includes.len(), 1);
+    assert!(profile.after_body_includes[0].contains("depth: 3"));
+    assert_eq!(profile.header_includes.iter().filter(|i| i.contains("quoin-list-of")).count(), 1);
+
+    let indent = OutlineOptions { indent: Some("1.5em); #evil".to_string()), ..OutlineOptions::default() };
+    assert!(Profile::new().set_outline_with(&indent).is_err());
+}
+
+#[test]
+fn test_bibliography_enables_citations() {
+    let mut profile = Profile::new();
+    profile.set_callouts();
+    profile.add_bibliography("refs.bib");
+    profile.override_variable("bibliography", "more.bib");
+    assert_eq!(profile.citations, Some(CitationMode::Citeproc));
+    assert_eq!(profile.input_format, "gfm");
+    assert!(profile.reader_extensions.contains(&"citations".to_string()));
+    assert_eq!(profile.metadata.bibliography, vec!["refs.bib", "more.bib"]);
+    // The bibliography filter is only registered once
+    assert_eq!(profile.lua_filters.len(), 2);
+
+    let front_matter = frontmatter::parse("---\ntitle: Paper\nbibliography: refs.bib\n---\n\n# Intro\n").unwrap();
+    assert!(front_matter.contains_key("bibliography"));
+    assert!(frontmatter::parse("# No front matter\n---\n").is_none());
+}
+
+#[test]
+fn test_pretty_code_options() {
+    let mut profile = Profile::new();
+    profile.set_pretty_code();
+    assert!(profile.header_includes[0].contains("#let quoin-code-defaults = (linenos: false, wrap: true)"));
+    assert!(profile.support_files.is_empty());
+
+    let mut profile = Profile::new();
+    profile.set_pretty_code_with(&PrettyCode {
+        line_numbers: true,
+        theme: CodeTheme::Dark,
+        wrap: false,
+    })
+    .unwrap();
+    let code_style = &profile.header_includes[0];
+    assert!(code_style.contains("#let quoin-code-defaults = (linenos: true, wrap: false)"));
+    assert!(code_style.contains("#set raw(theme: \""));
+    assert!(profile.support_files[0].path.ends_with("quoin-dark.tmTheme"));
+
+    let typo = PrettyCode { theme: CodeTheme::parse("drak"), ..PrettyCode::default() };
+    assert!(Profile::new().set_pretty_code_with(&typo).unwrap_err().to_string().contains("'drak'"));
+}
+
+#[test]
+fn test_typography_per_density() {
+    let mut profile = Profile::new();
+    profile.set_density("standard");
+    assert!(profile.before_body_includes.is_empty());
+
+    profile.set_density("ultra-dense");
+    profile
+        .set_typography(&Typography {
+            first_line_indent: Some("1.5em".to_string()),
+            justify: Some(false),
+            widow_orphan_control: Some(false),
+            ..Typography::default()
+        })
+        .unwrap();
+    assert_eq!(profile.before_body_includes[0], "#set par(leading: 0.45em, spacing: 0.75em)");
+    assert_eq!(
+        profile.before_body_includes[1],
+        "#set par(first-line-indent: 1.5em, justify: false)\n#set text(costs: (widow: 0%, orphan: 0%))"
+    );
+    assert_eq!(profile.typography.leading.as_deref(), Some("0.45em"));
+
+    // Changing the level replaces its rules and keeps the other settings
+    profile.set_density("standard");
+    assert_eq!(profile.before_body_includes.len(), 1);
+    assert_eq!(profile.typography.leading, None);
+    assert_eq!(profile.typography.first_line_indent.as_deref(), Some("1.5em"));
+    profile.set_density("dense");
+    profile.set_density("comfort");
+    assert_eq!(profile.before_body_includes.len(), 2);
+    assert_eq!(profile.before_body_includes[1], "#set par(leading: 0.8em, spacing: 1.4em)");
+
+    let invalid = Typography { leading: Some("1.2em)\n#panic(".to_string()), ..Typography::default() };
+    assert!(profile.set_typography(&invalid).is_err());
+}
+
+#[test]
+fn test_heading_style() {
+    let mut profile = Profile::new();
+    profile
+        .set_heading_style(&HeadingStyle {
+            numbering: Some("I.1".to_string()),
+            appendix: Some("A.1".to_string()),
+            sizes: vec!["18pt".to_string(), "14pt".to_string()],
+            weights: vec!["bold".to_string()],
+            colors: vec!["#1f4e79".to_string(), "navy".to_string()],
+            below: Some("0.8em".to_string()),
+            keep_with_next: Some(true),
+            page_break: Some(true),
+            ..HeadingStyle::default()
+        })
+        .unwrap();
+    assert_eq!(
+        profile.before_body_includes[0],
+        "#set heading(numbering: \"I.1\")\n\
+         #show heading.where(level: 1): set text(size: 18pt, weight: \"bold\", fill: rgb(\"#1f4e79\"))\n\
+         #show heading.where(level: 2): set text(size: 14pt, fill: navy)\n\
+         #show heading: set block(below: 0.8em, sticky: true)\n\
+         #show heading.where(level: 1): it => { pagebreak(weak: true); it }"
+    );
+    assert_eq!(profile.lua_filters.len(), 1);
+
+    // Layered settings keep earlier values
+    profile.set_heading_style(&HeadingStyle { numbering: Some("1.a".to_string()), ..HeadingStyle::default() }).unwrap();
+    assert_eq!(profile.headings.numbering.as_deref(), Some("1.a"));
+    assert_eq!(profile.headings.appendix.as_deref(), Some("A.1"));
+    assert_eq!(profile.lua_filters.len(), 1);
+
+    let invalid = HeadingStyle { colors: vec!["rgb(0,0,0))".to_string()], ..HeadingStyle::default() };
+    assert!(profile.set_heading_style(&invalid).is_err());
+}
+
+#[test]
+fn test_theme() {
+    let mut profile = Profile::new();
+    let prelude = profile.theme.prelude().unwrap();
+    assert!(prelude.contains("table-header: rgb(\"#e4e4e4\"), table-stroke: rgb(\"#888888\")"));
+    assert!(prelude.contains("callout-fill: c => c.lighten(92%)"));
+    assert!(!prelude.contains("#set page"));
+
+    profile.set_theme(&Theme::resolve("screen").unwrap()).unwrap();
+    assert!(profile.before_body_includes[0].starts_with("#set text(fill: rgb(\"#cdd6f4\"))"));
+    let prelude = profile.theme.prelude().unwrap();
+    assert!(prelude.contains("#set page(fill: rgb(\"#1e1e2e\"))"));
+    assert!(prelude.contains("callout-fill: c => c.darken(75%)"));
+
+    // Layered on top of the screen theme, from a file with its logo next to it
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("logo.svg"), "<svg xmlns=\"http://www.w3.org/2000/svg\"/>").unwrap();
+    std::fs::write(dir.path().join("brand.toml"), "primary = \"#1f4e79\"\nlogo = \"logo.svg\"\n[callouts]\nnote = \"navy\"\n")
+        .unwrap();
+    profile.set_theme(&Theme::load(&dir.path().join("brand.toml")).unwrap()).unwrap();
+    assert_eq!(profile.theme.primary.as_deref(), Some("#1f4e79"));
+    assert_eq!(profile.theme.callouts.note.as_deref(), Some("navy"));
+    assert_eq!(profile.theme.callouts.tip.as_deref(), Some("#a6e3a1"));
+    assert!(profile.theme.prelude().unwrap().contains("logo.svg\", width: 2.5cm)"));
+    assert_eq!(profile.support_files[0].path, std::path::absolute(dir.path().join("logo.svg")).unwrap());
+
+    assert!(Theme::resolve("poster").is_err());
+    let invalid = Theme { link: Some("blue)\n#panic(".to_string()), ..Theme::default() };
+    assert!(profile.set_theme(&invalid).is_err());
+}
+
+#[test]
+fn test_paper_and_orientation() {
+    let mut profile = Profile::new();
+    assert_eq!(profile.page_setup(), "");
+    profile.set_paper("Letter").unwrap();
+    assert_eq!(profile.metadata.papersize, "us-letter");
+    profile.set_paper("a5").unwrap();
+    assert_eq!(profile.metadata.papersize, "a5");
+    assert!(profile.page_size.is_none());
+
+    profile.set_paper("6inx9in").unwrap();
+    profile.set_landscape(true);
+    let setup = profile.page_setup();
+    assert!(setup.starts_with("#set page(flipped: true)\n#let quoin-page = (width: 6in, height: 9in)\n"));
+    assert!(setup.contains("#let quoin-pandoc-conf = conf"));
+
+    // Orientation blocks are registered once
+    profile.set_orientation_blocks();
+    profile.set_orientation_blocks();
+    assert_eq!(profile.lua_filters.len(), 1);
+
+    for invalid in ["a13", "6in", "0x9in", "10emx20em", "6in)x9in"] {
+        assert!(profile.set_paper(invalid).is_err(), "{}", invalid);
+    }
+
+    // -V papersize goes through the same validation
+    profile.override_variable("papersize", "legal");
+    assert_eq!(profile.metadata.papersize, "us-legal");
+    profile.override_variable("papersize", "a99");
+    assert_eq!(profile.metadata.papersize, "us-legal");
+    assert!(profile.page_size.is_none());
+}
+
+#[test]
+fn test_columns() {
+    let mut profile = Profile::new();
+    profile.set_conditionals();
+    profile.set_callouts();
+    profile.set_two_cols(true);
+    assert_eq!(profile.metadata.columns, 1);
+    assert_eq!(profile.columns.count(), 2);
+    // Sections are formed after conditionals and before callouts are unwrapped
+    assert!(profile.lua_filters[1].contains("#quoin-columns["));
+    assert_eq!(profile.lua_filters.len(), 3);
+
+    profile
+        .set_columns(&ColumnLayout {
+            count: Some(3),
+            gutter: Some(" 1em".to_string()),
+            rule: Some(true),
+            ..ColumnLayout::default()
+        })
+        .unwrap();
+    assert_eq!(profile.lua_filters.len(), 3);
+    let setup = profile.page_setup();
+    assert!(setup.starts_with(
+        "#let quoin-column-layout = (count: 3, gutter: 1em, rule: true, balance: false, margin-x: 2.5cm, margin-y: 3cm)"
+    ));
+    assert!(setup.contains("#let quoin-columns(body)"));
+    // Level-1 headings that break the page span the columns
+    assert!(!profile.level_one_page_breaks());
+    let mut report = Profile::new();
+    report.set_class(DocumentClass::Report);
+    assert!(report.level_one_page_breaks());
+
+    assert!(profile.set_columns(&ColumnLayout { count: Some(7), ..ColumnLayout::default() }).is_err());
+    assert!(profile.set_columns(&ColumnLayout { gutter: Some("wide".to_string()), ..ColumnLayout::default() }).is_err());
+    profile.override_variable("columns", "1");
+    assert_eq!(profile.page_setup(), "");
+}
+
+#[test]
+fn test_cheat_sheet() {
+    let mut profile = Profile::new();
+    profile.set_conditionals();
+    profile.set_callouts();
+    profile.set_cheat_sheet(4).unwrap();
+    profile.set_cheat_sheet(4).unwrap();
+    assert!(profile.landscape);
+    assert_eq!(profile.metadata.margin.x, "1cm");
+    assert_eq!(profile.columns.count(), 4);
+    // Sections are grouped into boxes before the column filter splits the document
+    assert!(profile.lua_filters[1].contains("quoin-cheat-box"));
+    assert!(profile.lua_filters[2].contains("#quoin-columns["));
+    assert_eq!(profile.lua_filters.len(), 5);
+    let setup = profile.page_setup();
+    assert!(setup.starts_with("#set page(flipped: true)\n#let quoin-page = (:)\n"));
+    assert!(setup.contains("#let quoin-title-block("));
+    assert!(profile.header_includes.iter().any(|include| include.contains("#let quoin-cheat-box(")));
+
+    assert!(Profile::new().set_cheat_sheet(2).is_err());
+}
+
+#[test]
+fn test_slides() {
+    let mut profile = Profile::new();
+    profile.set_conditionals();
+    profile.set_callouts();
+    profile.set_landscape(true);
+    profile.set_format("slides").unwrap();
+    profile.set_slides(&SlideOptions { speaker_notes: Some(true), ..SlideOptions::default() }).unwrap();
+    assert_eq!(profile.metadata.papersize, "presentation-16-9");
+    assert!(!profile.landscape);
+    // Slides are split before callouts unwrap the notes divs
+    assert!(profile.lua_filters[1].contains("#quoin-slide("));
+    assert_eq!(profile.lua_filters.len(), 3);
+    let setup = profile.page_setup();
+    assert!(setup.contains("#let quoin-slides = (speaker-notes: true)\n"));
+    assert!(setup.contains("#let quoin-slide(kind: \"slide\""));
+    assert!(setup.find("#let quoin-title-block(").unwrap() < setup.find("#let quoin-pandoc-conf").unwrap());
+
+    profile.set_format("document").unwrap();
+    assert!(profile.slides.is_some());
+    assert!(profile.set_format("poster").is_err());
+}
+
+#[test]
+fn test_document_class() {
+    let mut profile = Profile::new();
+    profile.set_class(DocumentClass::parse("letter").unwrap());
+    assert!(profile.page_setup().contains("#let quoin-letter-closing()"));
+    assert_eq!(profile.after_body_includes, vec!["#quoin-letter-closing()"]);
+
+    let front_matter = frontmatter::parse(
+        "---\nclass: letter\nsender: |\n  Ada Lovelace\n  12 St James's Square\nrecipient: [Charles Babbage, London]\n---\n",
+    )
+    .unwrap();
+    assert_eq!(
+        DocumentClass::Letter.fields_prelude(&front_matter).unwrap(),
+        "#let quoin-fields = (sender: \"Ada Lovelace\\n12 St James's Square\", recipient: (\"Charles Babbage\", \"London\",), \
+         subject: none, closing: none, signature: none)\n"
+    );
+    let error = DocumentClass::Memo.fields_prelude(&front_matter).unwrap_err().to_string();
+    assert!(error.contains("requires these front matter fields: to, from, subject"), "{}", error);
+
+    // -V class switches classes; only letters get a closing
+    profile.override_variable("class", "memo");
+    assert_eq!(profile.class, Some(DocumentClass::Memo));
+    assert!(profile.after_body_includes.is_empty());
+    assert!(DocumentClass::parse("thesis").is_err());
+}
+
+#[test]
+fn test_cjk_rules() {
+    let mut profile = Profile::new();
+    assert!(profile.cjk_rules().is_none());
+    profile.override_variable("lang", "zh");
+    assert_eq!(
+        profile.cjk_rules().unwrap(),
+        "#set text(font: ((name: \"Libertinus Serif\", covers: \"latin-in-cjk\"), \"Noto Serif CJK SC\", \"Source Han Serif SC\", \
+         \"Songti SC\", \"SimSun\",), cjk-latin-spacing: auto)\n\
+         #show raw: set text(font: (\"DejaVu Sans Mono\", \"Noto Sans CJK SC\", \"Source Han Sans SC\", \"PingFang SC\", \
+         \"Microsoft YaHei\", \"WenQuanYi Micro Hei\",))\n\
+         #set par(leading: 0.9em, justify: true, first-line-indent: 2em)"
+    );
+
+    // Configured fonts come first; typography settings are kept
+    profile.override_variable("lang", "ja");
+    profile.set_density("dense");
+    profile.set_fonts(&FontOptions { heading: vec!["Inter".to_string()], ..FontOptions::default() }).unwrap();
+    let rules = profile.cjk_rules().unwrap();
+    assert!(rules.contains("#show heading: set text(font: (\"Inter\", \"Noto Sans CJK JP\","));
+    assert!(rules.ends_with("#set par(justify: true, first-line-indent: 1em)"));
+    profile.override_variable("monofont", "Sarasa Mono J");
+    assert!(profile.cjk_rules().unwrap().contains("#show raw: set text(font: (\"Sarasa Mono J\", \"Noto Sans CJK JP\","));
+
+    profile.set_ruby();
+    profile.set_ruby();
+    assert_eq!(profile.lua_filters.len(), 1);
+    assert!(profile.header_includes[0].contains("#let quoin-ruby(base, ruby)"));
+}
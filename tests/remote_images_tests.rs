@@ -0,0 +1,113 @@
+mod common;
+
+use quoin::remote::{RemoteImageOptions, localize_remote_images};
+use mlua::{Function, Table};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+/// Serves `body` as a PNG for `requests` connections on a local port, returning the base URL.
+fn serve_png(body: &'static [u8], requests: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    format!("http://{}", addr)
+}
+
+fn options(cache: &tempfile::TempDir) -> RemoteImageOptions {
+    RemoteImageOptions {
+        timeout: Duration::from_secs(5),
+        cache_dir: cache.path().to_path_buf(),
+        ..RemoteImageOptions::default()
+    }
+}
+
+#[test]
+fn test_remote_images_are_cached() {
+    let cache = tempfile::tempdir().unwrap();
+    let base = serve_png(b"\x89PNG fake image", 1);
+    let markdown = format!("![Logo]({}/logo) and ![Local](local.png)", base);
+
+    let rewritten = localize_remote_images(&markdown, &options(&cache)).unwrap();
+    let path = rewritten.strip_prefix("![Logo](").unwrap().split(')').next().unwrap();
+    assert!(path.ends_with(".png"));
+    assert_eq!(std::fs::read(path).unwrap(), b"\x89PNG fake image");
+    assert!(rewritten.ends_with("![Local](local.png)"));
+
+    // The server only answers once: the second run must come from the cache, even offline
+    let offline = RemoteImageOptions { offline: true, ..options(&cache) };
+    assert_eq!(localize_remote_images(&markdown, &offline).unwrap(), rewritten);
+}
+
+#[test]
+fn test_offline_and_size_limits() {
+    let cache = tempfile::tempdir().unwrap();
+    let offline = RemoteImageOptions { offline: true, ..options(&cache) };
+    let markdown = "![A](https://example.invalid/a.png)\n![B](https://example.invalid/b.png)";
+
+    let error = localize_remote_images(markdown, &offline).unwrap_err().to_string();
+    assert!(error.contains("2 remote image(s)"));
+
+    let with_placeholder = RemoteImageOptions { placeholder: true, ..offline };
+    let rewritten = localize_remote_images(markdown, &with_placeholder).unwrap();
+    assert_eq!(rewritten.matches("placeholder.svg").count(), 2);
+
+    let base = serve_png(b"0123456789", 1);
+    let tiny = RemoteImageOptions { max_bytes: 4, ..options(&cache) };
+    let error = localize_remote_images(&format!("![Big]({}/big.png)", base), &tiny).unwrap_err();
+    assert!(error.to_string().contains("byte limit"));
+}
+
+#[test]
+fn test_remote_images_on_the_server() {
+    // The server restricts images to its working directory; downloaded images live in the cache instead
+    let cache = tempfile::tempdir().unwrap();
+    let sandbox = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    std::fs::write(outside.path().join("secret.png"), b"\x89PNG secret").unwrap();
+    let base = serve_png(b"\x89PNG fake image", 1);
+    let rewritten = localize_remote_images(&format!("![Logo]({}/logo.png)", base), &options(&cache)).unwrap();
+    let downloaded = rewritten.strip_prefix("![Logo](").unwrap().strip_suffix(')').unwrap().to_string();
+
+    let sandbox_dir = sandbox.path().to_str().unwrap();
+    let cache_dir = cache.path().to_str().unwrap();
+    let env = [
+        ("QUOIN_IMAGE_SANDBOX", sandbox_dir),
+        ("QUOIN_REMOTE_IMAGE_CACHE", cache_dir),
+        ("QUOIN_TYPST_DIR", sandbox_dir),
+    ];
+    let lua = common::load_filter(include_str!("../src/assets/lua/images.lua"), &env).unwrap();
+    let filters: Table = lua.globals().get("filters").unwrap();
+    let image: Function = filters.get::<_, Table>(1).unwrap().get("Image").unwrap();
+    let resolve = |src: &str| -> Option<String> {
+        let el = lua.create_table().unwrap();
+        el.set("src", src).unwrap();
+        el.set("caption", "caption").unwrap();
+        match image.call::<_, mlua::Value>(el).unwrap() {
+            mlua::Value::Table(el) => Some(Table::get::<_, String>(&el, "src").unwrap()),
+            _ => None,
+        }
+    };
+
+    let src = resolve(&downloaded).expect("the downloaded image is kept");
+    assert!(!src.starts_with('/') && src.ends_with(".png"), "{}", src);
+    assert_eq!(std::fs::read(sandbox.path().join(&src)).unwrap(), b"\x89PNG fake image");
+    // Other files outside the sandbox are still replaced by their description
+    assert_eq!(resolve(outside.path().join("secret.png").to_str().unwrap()), None);
+}
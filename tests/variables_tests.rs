@@ -0,0 +1,62 @@
+mod common;
+
+use mlua::{Function, Table};
+use quoin::variables::mark_escapes;
+
+const FILTER: &str = include_str!("../src/assets/lua/variables.lua");
+
+/// Runs the variables filter on `text` (as `Str` and `Space` inlines), with `meta` as the document metadata,
+/// and returns the resulting text, the warnings and whether the final report succeeded.
+fn substitute(text: &str, meta: &str, env: &[(&str, &str)]) -> (String, Vec<String>, mlua::Result<()>) {
+    let lua = common::load_filter(FILTER, env).unwrap();
+    let filters: Table = lua.globals().get("filters").unwrap();
+    let meta: Table = lua.load(meta).eval().unwrap();
+    let capture: Table = filters.get(1).unwrap();
+    capture.get::<_, Function>("Meta").unwrap().call::<_, ()>(meta).unwrap();
+
+    let inlines_filter: Table = filters.get(2).unwrap();
+    let inlines: Table = lua.load(format!("pandoc.Inlines({:?})", text)).eval().unwrap();
+    let result: Option<Table> = inlines_filter.get::<_, Function>("Inlines").unwrap().call(inlines.clone()).unwrap();
+    let stringify: Function = lua.load("pandoc.utils.stringify").eval().unwrap();
+    let text: String = stringify.call(result.unwrap_or(inlines)).unwrap();
+
+    let report: Table = filters.get(3).unwrap();
+    let reported = report.get::<_, Function>("Pandoc").unwrap().call::<_, ()>(());
+    let warnings: Vec<String> = lua.globals().get("warnings").unwrap();
+    (text, warnings, reported)
+}
+
+#[test]
+fn test_substitute_variables() {
+    let meta = r#"{ title = "Guide", version = "0.9", author = { name = "Ada" }, tags = pandoc.List({ "a", "b" }) }"#;
+    let env = [("QUOIN_VARIABLES", "version=1.2.0")];
+
+    let (text, warnings, reported) = substitute(
+        &mark_escapes("{{ version }} by {{author.name}} ({{tags}}), literal \\{{version}}, not a name {{ 1 + 1 }}"),
+        meta,
+        &env,
+    );
+    assert_eq!(text, "1.2.0 by Ada (a, b), literal {{version}}, not a name {{ 1 + 1 }}");
+    assert!(warnings.is_empty() && reported.is_ok());
+
+    let (text, warnings, _) = substitute("unknown {{missing}} and {{author}}", meta, &env);
+    assert_eq!(text, "unknown {{missing}} and {{author}}");
+    assert_eq!(warnings, vec!["[quoin] Warning: leaving 2 undefined template variable(s): missing, author as-is\n"]);
+
+    let strict = [("QUOIN_STRICT_VARIABLES", "1")];
+    let (_, _, reported) = substitute("{{missing}}", meta, &strict);
+    assert!(reported.unwrap_err().to_string().contains("[quoin] 1 undefined template variable(s): missing"));
+
+    let (text, _, _) = substitute("{{date}}", "{}", &[("SOURCE_DATE_EPOCH", "1700000000")]);
+    assert_eq!(text, "2023-11-14");
+}
+
+#[test]
+fn test_code_is_left_alone() {
+    let lua = common::load_filter(FILTER, &[("QUOIN_VARIABLES", "github.sha=abc")]).unwrap();
+    let filters: Table = lua.globals().get("filters").unwrap();
+    let filter: Table = filters.get(2).unwrap();
+    let code: Table = lua.load(format!("pandoc.Code({:?})", mark_escapes("${{ github.sha }} \\{{x}}"))).eval().unwrap();
+    let code: Table = filter.get::<_, Function>("Code").unwrap().call(code).unwrap();
+    assert_eq!(code.get::<_, String>("text").unwrap(), "${{ github.sha }} \\{{x}}");
+}
@@ -15,15 +15,24 @@ function App() {
   const [config, setConfig] = useState<Config>(() => {
     const saved = localStorage.getItem('quoin-config');
     const defaultConfig: Config = {
+      format: 'document',
+      class: '',
       density: 'standard',
-      two_cols: false,
-      latex_font: false,
+      columns: 1,
+      paper: 'a4',
+      landscape: false,
+      cheat_sheet: false,
+      font: '',
+      theme: 'default',
       alt_table: true,
       pretty_code: true,
+      callouts: true,
+      diagrams: true,
+      crossrefs: true,
       section_numbering: false,
       outline: false
     };
-    return saved ? JSON.parse(saved) : defaultConfig;
+    return saved ? { ...defaultConfig, ...JSON.parse(saved) } : defaultConfig;
   });
   const [liveMode, setLiveMode] = useState(true);
   const [isDarkMode, setIsDarkMode] = useState(() => {
@@ -1,4 +1,5 @@
 import { Config } from '../hooks/useConversion';
+import { useFonts } from '../hooks/useFonts';
 
 interface SidebarProps {
   config: Config;
@@ -8,6 +9,7 @@ interface SidebarProps {
 }
 
 export function Sidebar({ config, setConfig, liveMode, setLiveMode }: SidebarProps) {
+  const fonts = useFonts();
   const updateConfig = (key: keyof Config, value: any) => {
     setConfig({ ...config, [key]: value });
   };
@@ -29,6 +31,34 @@ export function Sidebar({ config, setConfig, liveMode, setLiveMode }: SidebarPro
         </label>
       </section>
 
+      <section>
+        <h3 className="text-xs font-bold uppercase tracking-widest text-muted-foreground mb-3">Format</h3>
+        <select
+          value={config.format ?? 'document'}
+          onChange={(e) => updateConfig('format', e.target.value)}
+          className="w-full bg-muted/50 border rounded-md p-2 text-sm focus:outline-none focus:ring-1 focus:ring-primary"
+        >
+          <option value="document">Document</option>
+          <option value="slides">Slides (16:9)</option>
+        </select>
+      </section>
+
+      <section>
+        <h3 className="text-xs font-bold uppercase tracking-widest text-muted-foreground mb-3">Document Class</h3>
+        <select
+          value={config.class ?? ''}
+          onChange={(e) => updateConfig('class', e.target.value)}
+          className="w-full bg-muted/50 border rounded-md p-2 text-sm focus:outline-none focus:ring-1 focus:ring-primary"
+        >
+          <option value="">From front matter</option>
+          <option value="article">Article</option>
+          <option value="report">Report</option>
+          <option value="letter">Letter</option>
+          <option value="memo">Memo</option>
+          <option value="resume">Resume</option>
+        </select>
+      </section>
+
       <section>
         <h3 className="text-xs font-bold uppercase tracking-widest text-muted-foreground mb-3">Layout Density</h3>
         <select
@@ -43,14 +73,82 @@ export function Sidebar({ config, setConfig, liveMode, setLiveMode }: SidebarPro
         </select>
       </section>
 
+      <section>
+        <h3 className="text-xs font-bold uppercase tracking-widest text-muted-foreground mb-3">Paper</h3>
+        <select
+          value={config.paper ?? 'a4'}
+          onChange={(e) => updateConfig('paper', e.target.value)}
+          className="w-full bg-muted/50 border rounded-md p-2 text-sm focus:outline-none focus:ring-1 focus:ring-primary"
+        >
+          <option value="a4">A4</option>
+          <option value="a5">A5</option>
+          <option value="letter">Letter</option>
+          <option value="legal">Legal</option>
+        </select>
+      </section>
+
+      <section>
+        <h3 className="text-xs font-bold uppercase tracking-widest text-muted-foreground mb-3">Columns</h3>
+        <select
+          value={config.columns ?? 1}
+          onChange={(e) => updateConfig('columns', Number(e.target.value))}
+          className="w-full bg-muted/50 border rounded-md p-2 text-sm focus:outline-none focus:ring-1 focus:ring-primary"
+        >
+          <option value={1}>One</option>
+          <option value={2}>Two</option>
+          <option value={3}>Three</option>
+          <option value={4}>Four</option>
+        </select>
+      </section>
+
+      <section>
+        <h3 className="text-xs font-bold uppercase tracking-widest text-muted-foreground mb-3">Font</h3>
+        <select
+          value={config.font ?? ''}
+          onChange={(e) => updateConfig('font', e.target.value)}
+          className="w-full bg-muted/50 border rounded-md p-2 text-sm focus:outline-none focus:ring-1 focus:ring-primary"
+        >
+          <option value="">Default</option>
+          <optgroup label="Pairings">
+            <option value="academic">Academic (LaTeX)</option>
+            <option value="modern-sans">Modern Sans</option>
+            <option value="technical">Technical</option>
+          </optgroup>
+          {fonts.length > 0 && (
+            <optgroup label="Installed">
+              {fonts.map((font) => (
+                <option key={font.name} value={font.name}>
+                  {font.name}{font.scripts.length > 0 ? ` (${font.scripts.join(', ')})` : ''}
+                </option>
+              ))}
+            </optgroup>
+          )}
+        </select>
+      </section>
+
+      <section>
+        <h3 className="text-xs font-bold uppercase tracking-widest text-muted-foreground mb-3">Theme</h3>
+        <select
+          value={config.theme ?? 'default'}
+          onChange={(e) => updateConfig('theme', e.target.value)}
+          className="w-full bg-muted/50 border rounded-md p-2 text-sm focus:outline-none focus:ring-1 focus:ring-primary"
+        >
+          <option value="default">Default (print)</option>
+          <option value="screen">Screen (dark)</option>
+        </select>
+      </section>
+
       <section className="space-y-4">
         <h3 className="text-xs font-bold uppercase tracking-widest text-muted-foreground mb-1">Display Options</h3>
 
         {[
-          { label: 'Two Columns', key: 'two_cols' },
-          { label: 'LaTeX Font', key: 'latex_font' },
+          { label: 'Landscape', key: 'landscape' },
+          { label: 'Cheat Sheet', key: 'cheat_sheet' },
           { label: 'Alt Tables', key: 'alt_table' },
           { label: 'Pretty Code', key: 'pretty_code' },
+          { label: 'Callouts', key: 'callouts' },
+          { label: 'Diagrams', key: 'diagrams' },
+          { label: 'Cross-References', key: 'crossrefs' },
           { label: 'Section Numbering', key: 'section_numbering' },
           { label: 'Outline (TOC)', key: 'outline' }
         ].map(({ label, key }) => (
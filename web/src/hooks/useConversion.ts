@@ -4,11 +4,27 @@ import { useState, useCallback, useEffect } from 'react';
  * Configuration for the document conversion.
  */
 export interface Config {
+  /** "document" or "slides". */
+  format: string;
+  /** Document class ("article", "report", "letter", "memo", "resume"); empty for the front matter's. */
+  class: string;
   density: string;
-  two_cols: boolean;
-  latex_font: boolean;
+  /** Number of columns, 1 to 4. */
+  columns: number;
+  /** Paper size, e.g. "a4" or "letter". */
+  paper: string;
+  landscape: boolean;
+  /** Cheat-sheet layout: landscape, boxed sections, 3 columns (or 4). */
+  cheat_sheet: boolean;
+  /** Font pairing name or body font family; empty for the default font. */
+  font: string;
+  /** Built-in color theme: "default" or "screen". */
+  theme: string;
   alt_table: boolean;
   pretty_code: boolean;
+  callouts: boolean;
+  diagrams: boolean;
+  crossrefs: boolean;
   section_numbering: boolean;
   outline: boolean;
 }
@@ -0,0 +1,27 @@
+import { useState, useEffect } from 'react';
+
+/**
+ * A font family available to the Typst engine, as returned by `/api/fonts`.
+ */
+export interface FontFamily {
+  name: string;
+  variants: { style: string; weight: number; stretch: string }[];
+  scripts: string[];
+  source: string;
+}
+
+/**
+ * Hook to fetch the font families the server can typeset with.
+ */
+export function useFonts() {
+  const [fonts, setFonts] = useState<FontFamily[]>([]);
+
+  useEffect(() => {
+    fetch('/api/fonts')
+      .then((response) => (response.ok ? response.json() : []))
+      .then(setFonts)
+      .catch(() => setFonts([]));
+  }, []);
+
+  return fonts;
+}